@@ -282,6 +282,61 @@ pub fn watched_path(_source_file_path: &'static str, _asset_path: &'static str)
     PathBuf::from("")
 }
 
+/// Embeds the bytes of the given files into the current binary and returns a [`Dir`] holding
+/// them, so that a [`MemoryAssetReader`] can read them without touching the filesystem.
+///
+/// This accepts the path `&str` of a directory (relative to the current file) as the first
+/// parameter, followed by a list of file paths relative to that directory. The assets are read
+/// with those relative paths. Files ending in `.meta` are stored as the meta file of the asset
+/// they are named after.
+///
+/// Unlike [`embedded_asset`], this does not register anything with the `embedded`
+/// [`AssetSource`]. Serving the [`Dir`] from the default source instead lets a shipped build read
+/// its assets from the binary using the same asset paths, with no change to the code loading
+/// them:
+///
+/// ```no_run
+/// # use bevy_app::App;
+/// # use bevy_asset::{embedded_dir, io::{memory::MemoryAssetReader, AssetSource, AssetSourceId}, AssetApp};
+/// # let mut app = App::new();
+/// let dir = embedded_dir!("../assets", ["rock.png", "rock.png.meta", "sounds/rumble.ogg"]);
+/// app.register_asset_source(
+///     AssetSourceId::Default,
+///     AssetSource::build().with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+/// );
+/// ```
+///
+/// Like [`embedded_asset`], this uses the [`include_bytes`] macro internally and _will not_
+/// reallocate the bytes.
+///
+/// [`embedded_asset`]: crate::embedded_asset
+#[macro_export]
+macro_rules! embedded_dir {
+    ($dir_path: expr, [$($path: expr),* $(,)?]) => {{
+        let dir = $crate::io::memory::Dir::default();
+        $(
+            $crate::io::embedded::_insert_embedded_file(
+                &dir,
+                $path,
+                include_bytes!(concat!($dir_path, "/", $path)),
+            );
+        )*
+        dir
+    }};
+}
+
+/// Implementation detail of `embedded_dir`, do not use this!
+///
+/// Inserts `bytes` into `dir` as the asset at `path`, or as the meta file of the asset `path` is
+/// named after if it ends in `.meta`.
+#[doc(hidden)]
+pub fn _insert_embedded_file(dir: &Dir, path: &str, bytes: &'static [u8]) {
+    match path.strip_suffix(".meta") {
+        Some(asset_path) => dir.insert_meta(Path::new(asset_path), bytes),
+        None => dir.insert_asset(Path::new(path), bytes),
+    }
+}
+
 /// Loads an "internal" asset by embedding the string stored in the given `path_str` and associates it with the given handle.
 #[macro_export]
 macro_rules! load_internal_asset {
@@ -333,8 +388,54 @@ macro_rules! load_internal_binary_asset {
 
 #[cfg(test)]
 mod tests {
-    use super::{EmbeddedAssetRegistry, _embedded_asset_path};
-    use std::path::Path;
+    use super::{EmbeddedAssetRegistry, _embedded_asset_path, _insert_embedded_file};
+    use crate::io::{memory::MemoryAssetReader, AssetReader, AssetReaderError, Reader};
+    use alloc::vec::Vec;
+    use bevy_tasks::block_on;
+    use futures_lite::StreamExt;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn embedded_dir_embeds_nested_directories() {
+        // the crate's own sources are as good a directory tree as any
+        let dir = crate::embedded_dir!("..", ["mod.rs", "embedded/embedded_watcher.rs"]);
+        _insert_embedded_file(&dir, "embedded/embedded_watcher.rs.meta", b"meta");
+        let reader = MemoryAssetReader { root: dir };
+        let read = |path: &'static str, meta: bool| {
+            block_on(async {
+                let mut bytes = Vec::new();
+                if meta {
+                    let mut file = reader.read_meta(Path::new(path)).await?;
+                    Reader::read_to_end(&mut file, &mut bytes).await?;
+                } else {
+                    let mut file = reader.read(Path::new(path)).await?;
+                    Reader::read_to_end(&mut file, &mut bytes).await?;
+                }
+                Ok::<_, AssetReaderError>(bytes)
+            })
+        };
+        let nested: Vec<PathBuf> = block_on(async {
+            reader
+                .read_directory(Path::new("embedded"))
+                .await
+                .unwrap()
+                .collect()
+                .await
+        });
+        assert_eq!(nested, [PathBuf::from("embedded/embedded_watcher.rs")]);
+        assert_eq!(
+            read("mod.rs", false),
+            Ok(include_bytes!("../mod.rs").to_vec())
+        );
+        assert_eq!(
+            read("embedded/embedded_watcher.rs", false),
+            Ok(include_bytes!("embedded_watcher.rs").to_vec())
+        );
+        assert_eq!(
+            read("embedded/embedded_watcher.rs", true),
+            Ok(b"meta".to_vec())
+        );
+    }
 
     // Relative paths show up if this macro is being invoked by a local crate.
     // In this case we know the relative path is a sub- path of the workspace
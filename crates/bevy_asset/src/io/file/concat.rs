@@ -0,0 +1,125 @@
+use super::{unblock, DiscardingSeek, FileAssetReader};
+use crate::io::{AssetReaderError, AsyncSeekForward, Reader};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use bevy_utils::synccell::SyncCell;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::AsyncRead;
+use futures_lite::{ready, StreamExt};
+use std::path::{Path, PathBuf};
+
+type OpenFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Box<dyn Reader>, AssetReaderError>> + Send + 'a>>;
+
+impl FileAssetReader {
+    /// Returns a reader serving the contents of every file in the directory at `path`, sorted by
+    /// path and concatenated into a single stream. Meta files and subdirectories are skipped.
+    ///
+    /// Only one file is open at a time: each file is opened once the previous one has been read
+    /// to the end, and closed as soon as it is exhausted.
+    pub async fn read_directory_concat<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<impl Reader + 'a, AssetReaderError> {
+        let entries = self.open_directory_entries(path).await?;
+        let mut entries: Vec<(PathBuf, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let full_path = self.full_path(&entry);
+                (entry, full_path)
+            })
+            .collect()
+            .await;
+        entries = unblock(move || {
+            entries.retain(|(_, full_path)| full_path.is_file());
+            entries
+        })
+        .await;
+        let mut paths: Vec<PathBuf> = entries.into_iter().map(|(path, _)| path).collect();
+        paths.sort_unstable();
+        Ok(ConcatReader {
+            files: ConcatFiles {
+                reader: self,
+                paths: paths.into(),
+                current: None,
+                opening: None,
+            },
+            seek: DiscardingSeek::default(),
+        })
+    }
+}
+
+/// A [`Reader`] that reads a list of asset files back-to-back, opening them one at a time.
+struct ConcatReader<'a> {
+    files: ConcatFiles<'a>,
+    seek: DiscardingSeek,
+}
+
+/// The files read by a [`ConcatReader`], read as a single stream.
+struct ConcatFiles<'a> {
+    reader: &'a FileAssetReader,
+    paths: VecDeque<PathBuf>,
+    current: Option<Box<dyn Reader>>,
+    // the open future is not `Sync`, but it is only ever accessed through `&mut self`
+    opening: Option<SyncCell<OpenFuture<'a>>>,
+}
+
+impl AsyncRead for ConcatFiles<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(current) = &mut this.current {
+                let n = ready!(Pin::new(current).poll_read(cx, buf))?;
+                if n > 0 || buf.is_empty() {
+                    return Poll::Ready(Ok(n));
+                }
+                this.current = None;
+            }
+            if this.opening.is_none() {
+                let Some(path) = this.paths.pop_front() else {
+                    return Poll::Ready(Ok(0));
+                };
+                let reader = this.reader;
+                let full_path = reader.full_path(&path);
+                this.opening = Some(SyncCell::new(Box::pin(reader.open_reader(full_path))));
+            }
+            let opening = this.opening.as_mut().unwrap().get();
+            let result = ready!(opening.as_mut().poll(cx));
+            this.opening = None;
+            this.current = Some(result.map_err(std::io::Error::other)?);
+        }
+    }
+}
+
+impl AsyncRead for ConcatReader<'_> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        this.seek.poll_read(Pin::new(&mut this.files), cx, buf)
+    }
+}
+
+impl AsyncSeekForward for ConcatReader<'_> {
+    fn poll_seek_forward(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        offset: u64,
+    ) -> Poll<std::io::Result<u64>> {
+        // the files cannot be seeked as one, so skip ahead by reading and discarding
+        let this = self.get_mut();
+        this.seek
+            .poll_seek_forward(Pin::new(&mut this.files), cx, offset)
+    }
+}
+
+impl Reader for ConcatReader<'_> {}
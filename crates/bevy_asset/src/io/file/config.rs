@@ -0,0 +1,221 @@
+#[cfg(windows)]
+use super::ReadShareMode;
+#[cfg(feature = "file_watcher")]
+use super::{resolve_root_path, FileWatcher};
+#[cfg(feature = "multi_threaded")]
+use super::{throttle::ReadRateLimit, BlockingExecutor};
+use super::{
+    BasePathSource, FileAssetReader, FileAssetWriter, ReadRetry, RootResolver, WriteTransform,
+    DEFAULT_BUSY_RETRY,
+};
+use crate::io::{AssetSourceBuilder, ErasedAssetReader, ErasedAssetWriter};
+#[cfg(feature = "file_watcher")]
+use crate::io::{AssetSourceEvent, AssetWatcher};
+use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
+use bevy_ecs::resource::Resource;
+#[cfg(any(feature = "multi_threaded", feature = "file_watcher"))]
+use core::time::Duration;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "file_watcher")]
+use tracing::warn;
+
+/// Settings for the [`FileAssetReader`] and [`FileAssetWriter`] of the default asset source,
+/// gathered in one place.
+///
+/// Insert this resource before adding the [`AssetPlugin`](crate::AssetPlugin) to apply it:
+///
+/// ```no_run
+/// # use bevy_app::App;
+/// # use bevy_asset::{io::file::FileIoConfig, AssetPlugin};
+/// App::new()
+///     .insert_resource(FileIoConfig {
+///         max_directory_entries: Some(10_000),
+///         ..Default::default()
+///     })
+///     .add_plugins(AssetPlugin::default());
+/// ```
+///
+/// The defaults match a reader and writer created with [`FileAssetReader::new`] and
+/// [`FileAssetWriter::new`]. It has no effect if the default source was registered explicitly.
+#[derive(Resource, Clone)]
+pub struct FileIoConfig {
+    /// The [`AssetServer::descriptor_limit`](crate::AssetServer::descriptor_limit) of the asset
+    /// server. `None` keeps the default for the platform. Unlike the other settings, this applies
+    /// even if the default source was registered explicitly.
+    pub descriptor_limit: Option<usize>,
+    /// The strategies tried in order to resolve the base path, see
+    /// [`FileAssetReader::new_with_base_path_order`]. `None` resolves it as usual.
+    pub base_path_order: Option<Vec<BasePathSource>>,
+    /// See [`FileAssetReader::with_not_found_retry`].
+    pub not_found_retry: Option<ReadRetry>,
+    /// See [`FileAssetReader::with_busy_retry`].
+    pub busy_retry: Option<ReadRetry>,
+    /// See [`FileAssetReader::with_stdin_path`].
+    pub stdin_path: Option<PathBuf>,
+    /// See [`FileAssetReader::with_max_directory_entries`].
+    pub max_directory_entries: Option<usize>,
+    /// See [`FileAssetReader::with_dangling_symlink_errors`].
+    pub dangling_symlink_errors: bool,
+    /// See [`FileAssetReader::with_auto_decompress`].
+    #[cfg(feature = "compression")]
+    pub auto_decompress: bool,
+    /// See [`FileAssetReader::with_slow_read_warn_threshold`].
+    #[cfg(feature = "multi_threaded")]
+    pub slow_read_warn_threshold: Option<Duration>,
+    /// See [`FileAssetReader::with_read_rate_limit`]. Each reader created from this config has
+    /// its own limit.
+    #[cfg(feature = "multi_threaded")]
+    pub read_rate_limit: Option<u64>,
+    /// See [`FileAssetReader::with_read_share_mode`].
+    #[cfg(windows)]
+    pub read_share_mode: ReadShareMode,
+    /// See [`FileAssetReader::with_blocking_executor`].
+    #[cfg(feature = "multi_threaded")]
+    pub blocking_executor: Option<Arc<dyn BlockingExecutor>>,
+    /// See [`FileAssetReader::with_root_resolver`].
+    pub root_resolver: Option<Arc<RootResolver>>,
+    /// See [`FileAssetWriter::with_write_transform`].
+    pub write_transform: Option<Arc<WriteTransform>>,
+    /// See [`FileAssetWriter::with_write_buffer`].
+    pub write_buffer_size: Option<usize>,
+}
+
+impl Default for FileIoConfig {
+    fn default() -> Self {
+        Self {
+            descriptor_limit: None,
+            base_path_order: None,
+            not_found_retry: None,
+            busy_retry: Some(DEFAULT_BUSY_RETRY),
+            stdin_path: None,
+            max_directory_entries: None,
+            dangling_symlink_errors: false,
+            #[cfg(feature = "compression")]
+            auto_decompress: false,
+            #[cfg(feature = "multi_threaded")]
+            slow_read_warn_threshold: None,
+            #[cfg(feature = "multi_threaded")]
+            read_rate_limit: None,
+            #[cfg(windows)]
+            read_share_mode: ReadShareMode::default(),
+            #[cfg(feature = "multi_threaded")]
+            blocking_executor: None,
+            root_resolver: None,
+            write_transform: None,
+            write_buffer_size: None,
+        }
+    }
+}
+
+impl FileIoConfig {
+    /// Creates a [`FileAssetReader`] at `path` with these settings.
+    pub fn reader<P: AsRef<Path>>(&self, path: P) -> FileAssetReader {
+        FileAssetReader {
+            not_found_retry: self.not_found_retry,
+            busy_retry: self.busy_retry,
+            stdin_path: self.stdin_path.clone(),
+            max_directory_entries: self.max_directory_entries,
+            dangling_symlink_errors: self.dangling_symlink_errors,
+            #[cfg(feature = "compression")]
+            auto_decompress: self.auto_decompress,
+            #[cfg(feature = "multi_threaded")]
+            slow_read_warn_threshold: self.slow_read_warn_threshold,
+            #[cfg(feature = "multi_threaded")]
+            read_rate_limit: self
+                .read_rate_limit
+                .map(|bytes_per_sec| Arc::new(ReadRateLimit::new(bytes_per_sec))),
+            #[cfg(windows)]
+            read_share_mode: self.read_share_mode,
+            #[cfg(feature = "multi_threaded")]
+            blocking_executor: self.blocking_executor.clone(),
+            root_resolver: self.root_resolver.clone(),
+            ..match &self.base_path_order {
+                Some(order) => FileAssetReader::new_with_base_path_order(path, order),
+                None => FileAssetReader::new(path),
+            }
+        }
+    }
+
+    /// Creates a [`FileAssetWriter`] at `path` with these settings.
+    pub fn writer<P: AsRef<Path> + core::fmt::Debug>(
+        &self,
+        path: P,
+        create_root: bool,
+    ) -> FileAssetWriter {
+        FileAssetWriter {
+            write_transform: self.write_transform.clone(),
+            write_buffer_size: self.write_buffer_size,
+            ..match &self.base_path_order {
+                Some(order) => FileAssetWriter::new_with_base_path_order(path, create_root, order),
+                None => FileAssetWriter::new(path, create_root),
+            }
+        }
+    }
+
+    /// Returns the platform default [`AssetSourceBuilder`] for `path` and `processed_path`, with
+    /// its readers and writers created with these settings. Its watchers watch the root paths
+    /// those readers resolve, which differ from the platform default with a `base_path_order`.
+    pub(crate) fn default_source(
+        &self,
+        path: &str,
+        processed_path: Option<&str>,
+    ) -> AssetSourceBuilder {
+        let source = AssetSourceBuilder::platform_default(path, processed_path)
+            .with_reader(self.reader_builder(path))
+            .with_writer(self.writer_builder(path));
+        #[cfg(feature = "file_watcher")]
+        let source = source.with_watcher(self.watcher_builder(path));
+        match processed_path {
+            #[cfg(feature = "file_watcher")]
+            Some(processed_path) => source
+                .with_processed_reader(self.reader_builder(processed_path))
+                .with_processed_writer(self.writer_builder(processed_path))
+                .with_processed_watcher(self.watcher_builder(processed_path)),
+            #[cfg(not(feature = "file_watcher"))]
+            Some(processed_path) => source
+                .with_processed_reader(self.reader_builder(processed_path))
+                .with_processed_writer(self.writer_builder(processed_path)),
+            None => source,
+        }
+    }
+
+    fn reader_builder(
+        &self,
+        path: &str,
+    ) -> impl FnMut() -> Box<dyn ErasedAssetReader> + Send + Sync + use<> {
+        let (config, path) = (self.clone(), path.to_string());
+        move || Box::new(config.reader(&path))
+    }
+
+    fn writer_builder(
+        &self,
+        path: &str,
+    ) -> impl FnMut(bool) -> Option<Box<dyn ErasedAssetWriter>> + Send + Sync + use<> {
+        let (config, path) = (self.clone(), path.to_string());
+        move |create_root| Some(Box::new(config.writer(&path, create_root)))
+    }
+
+    #[cfg(feature = "file_watcher")]
+    fn watcher_builder(
+        &self,
+        path: &str,
+    ) -> impl FnMut(crossbeam_channel::Sender<AssetSourceEvent>) -> Option<Box<dyn AssetWatcher>>
+           + Send
+           + Sync
+           + use<> {
+        let (order, path) = (self.base_path_order.clone(), path.to_string());
+        move |sender| {
+            // resolved like the root path of the reader, so the watcher sees the files it reads
+            let (root_path, _) = resolve_root_path(Path::new(&path), order.as_deref());
+            if !root_path.exists() {
+                warn!("Skip creating file watcher because path {root_path:?} does not exist.");
+                return None;
+            }
+            let watcher = FileWatcher::new(root_path.clone(), sender, Duration::from_millis(300))
+                .unwrap_or_else(|e| {
+                    panic!("Failed to create file watcher from path {root_path:?}, {e:?}")
+                });
+            Some(Box::new(watcher))
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use super::DiscardingSeek;
+use crate::io::{AsyncSeekForward, Reader};
+use alloc::boxed::Box;
+use async_compression::futures::bufread::{GzipDecoder, XzDecoder, ZstdDecoder};
+use bevy_utils::synccell::SyncCell;
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::AsyncRead;
+use futures_lite::{io::BufReader, AsyncBufReadExt};
+
+const GZIP_MAGIC: &[u8] = &[0x1F, 0x8B];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xB5, 0x2F, 0xFD];
+const XZ_MAGIC: &[u8] = &[0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00];
+
+/// A [`Reader`] that transparently decompresses gzip, zstd and xz streams, detected by their magic
+/// bytes rather than by file extension. Any other content is passed through unchanged.
+pub struct DecompressReader {
+    // some decoders are not `Sync`, but they are only ever accessed through `&mut self`
+    inner: SyncCell<Box<dyn AsyncRead + Unpin + Send>>,
+    seek: DiscardingSeek,
+}
+
+impl DecompressReader {
+    /// Peeks the leading bytes of `reader` and wraps it in the matching decompressor.
+    pub async fn new<R: AsyncRead + Unpin + Send + 'static>(reader: R) -> std::io::Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let header = reader.fill_buf().await?;
+        let inner: Box<dyn AsyncRead + Unpin + Send> = if header.starts_with(GZIP_MAGIC) {
+            Box::new(GzipDecoder::new(reader))
+        } else if header.starts_with(ZSTD_MAGIC) {
+            Box::new(ZstdDecoder::new(reader))
+        } else if header.starts_with(XZ_MAGIC) {
+            Box::new(XzDecoder::new(reader))
+        } else {
+            Box::new(reader)
+        };
+        Ok(Self {
+            inner: SyncCell::new(inner),
+            seek: DiscardingSeek::default(),
+        })
+    }
+}
+
+impl AsyncRead for DecompressReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        this.seek.poll_read(Pin::new(this.inner.get()), cx, buf)
+    }
+}
+
+impl AsyncSeekForward for DecompressReader {
+    fn poll_seek_forward(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        offset: u64,
+    ) -> Poll<std::io::Result<u64>> {
+        // decompressed streams cannot seek, so skip ahead by reading and discarding
+        let this = self.get_mut();
+        this.seek
+            .poll_seek_forward(Pin::new(this.inner.get()), cx, offset)
+    }
+}
+
+impl Reader for DecompressReader {}
@@ -0,0 +1,186 @@
+use crate::io::{AsyncSeekForward, Reader};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+use bevy_tasks::BoxedFuture;
+use bevy_utils::synccell::SyncCell;
+use core::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::AsyncRead;
+use futures_lite::ready;
+use parking_lot::Mutex;
+use std::{
+    fs::File,
+    io::{Error, ErrorKind, Read, Seek, SeekFrom},
+};
+
+/// Runs the blocking filesystem calls of a [`FileAssetReader`](super::FileAssetReader) on an
+/// executor of the application's choosing, for example with `tokio::task::spawn_blocking`:
+///
+/// ```ignore
+/// struct TokioExecutor;
+///
+/// impl BlockingExecutor for TokioExecutor {
+///     fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> BoxedFuture<'static, ()> {
+///         Box::pin(async move {
+///             let _ = tokio::task::spawn_blocking(task).await;
+///         })
+///     }
+/// }
+/// ```
+pub trait BlockingExecutor: Send + Sync + 'static {
+    /// Runs `task` on a thread where blocking is allowed. The returned future completes once
+    /// `task` has run, or once it is known that it never will.
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) -> BoxedFuture<'static, ()>;
+}
+
+/// The error a [`run_blocking`] call fails with when the executor drops its task.
+#[derive(thiserror::Error, Debug)]
+#[error("blocking task was cancelled")]
+struct Cancelled;
+
+/// Returns `true` if `error` is the one a [`run_blocking`] call fails with when the executor
+/// drops its task.
+pub(super) fn is_cancelled(error: &Error) -> bool {
+    error.get_ref().is_some_and(|inner| inner.is::<Cancelled>())
+}
+
+/// Runs `f` on `executor`, returning its result, or an error if the executor dropped it.
+pub(super) async fn run_blocking<T: Send + 'static>(
+    executor: &dyn BlockingExecutor,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> std::io::Result<T> {
+    let result = Arc::new(Mutex::new(None));
+    let slot = result.clone();
+    executor
+        .spawn_blocking(Box::new(move || *slot.lock() = Some(f())))
+        .await;
+    let value = result.lock().take();
+    value.ok_or_else(|| Error::new(ErrorKind::Interrupted, Cancelled))
+}
+
+/// How many bytes a [`BlockingFileReader`] reads with each blocking call.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The file, handed back by a blocking call along with the chunk read from it.
+type ChunkRead = std::io::Result<(File, std::io::Result<Vec<u8>>)>;
+
+/// A [`Reader`] for a file opened through a [`BlockingExecutor`]. The file is read a chunk at a
+/// time, each with a blocking call run on the executor, rather than loaded into memory at once.
+pub(super) struct BlockingFileReader {
+    executor: Arc<dyn BlockingExecutor>,
+    /// The file, while no chunk is being read from it.
+    file: Option<File>,
+    reading: Option<SyncCell<BoxedFuture<'static, ChunkRead>>>,
+    chunk: Vec<u8>,
+    consumed: usize,
+    /// How many bytes to skip before the next one is read, after seeking forward.
+    skip: u64,
+    position: u64,
+}
+
+impl BlockingFileReader {
+    pub(super) fn new(executor: Arc<dyn BlockingExecutor>, file: File) -> Self {
+        Self {
+            executor,
+            file: Some(file),
+            reading: None,
+            chunk: Vec::new(),
+            consumed: 0,
+            skip: 0,
+            position: 0,
+        }
+    }
+
+    /// Skips as much of the pending skip as the current chunk holds.
+    fn skip_in_chunk(&mut self) {
+        let remaining = self.chunk.len() - self.consumed;
+        let n = remaining.min(usize::try_from(self.skip).unwrap_or(usize::MAX));
+        self.consumed += n;
+        self.skip -= n as u64;
+    }
+}
+
+/// Seeks `skip` bytes forward in `file`, then reads up to [`CHUNK_SIZE`] bytes from it.
+fn read_chunk(file: &mut File, skip: u64) -> std::io::Result<Vec<u8>> {
+    if skip > 0 {
+        let skip = i64::try_from(skip).map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+        file.seek(SeekFrom::Current(skip))?;
+    }
+    let mut chunk = vec![0; CHUNK_SIZE];
+    let n = loop {
+        match file.read(&mut chunk) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            result => break result?,
+        }
+    };
+    chunk.truncate(n);
+    Ok(chunk)
+}
+
+impl AsyncRead for BlockingFileReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        loop {
+            this.skip_in_chunk();
+            let available = &this.chunk[this.consumed..];
+            if !available.is_empty() || buf.is_empty() {
+                let n = available.len().min(buf.len());
+                buf[..n].copy_from_slice(&available[..n]);
+                this.consumed += n;
+                this.position += n as u64;
+                return Poll::Ready(Ok(n));
+            }
+            if this.reading.is_none() {
+                let Some(mut file) = this.file.take() else {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Interrupted,
+                        "the file was lost with a blocking read that was cancelled",
+                    )));
+                };
+                let (executor, skip) = (this.executor.clone(), mem::take(&mut this.skip));
+                this.reading = Some(SyncCell::new(Box::pin(async move {
+                    run_blocking(&*executor, move || {
+                        let chunk = read_chunk(&mut file, skip);
+                        (file, chunk)
+                    })
+                    .await
+                })));
+            }
+            let reading = this.reading.as_mut().expect("a read was started above");
+            let result = ready!(reading.get().as_mut().poll(cx));
+            this.reading = None;
+            let (file, chunk) = result?;
+            this.file = Some(file);
+            this.chunk = chunk?;
+            this.consumed = 0;
+            if this.chunk.is_empty() {
+                // the end of the file
+                return Poll::Ready(Ok(0));
+            }
+        }
+    }
+}
+
+impl AsyncSeekForward for BlockingFileReader {
+    fn poll_seek_forward(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        offset: u64,
+    ) -> Poll<std::io::Result<u64>> {
+        // the skip is applied to the buffered chunk, and to the file by the next blocking read
+        let this = self.get_mut();
+        this.skip += offset;
+        this.position += offset;
+        this.skip_in_chunk();
+        Poll::Ready(Ok(this.position))
+    }
+}
+
+impl Reader for BlockingFileReader {}
@@ -0,0 +1,66 @@
+use super::FileAssetReader;
+use parking_lot::Mutex;
+use std::io::{Error, ErrorKind};
+
+/// Failures to inject into the next file opens of a [`FileAssetReader`], to exercise error
+/// handling without having to provoke the error from the operating system. See
+/// [`FileAssetReader::inject_fault`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FaultSpec {
+    count: usize,
+    error: InjectedError,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InjectedError {
+    Kind(ErrorKind),
+    Os(i32),
+}
+
+impl FaultSpec {
+    /// Fails the next `count` opens with an error of the given `kind`.
+    pub fn kind(count: usize, kind: ErrorKind) -> Self {
+        Self {
+            count,
+            error: InjectedError::Kind(kind),
+        }
+    }
+
+    /// Fails the next `count` opens with the operating system error `code`, for example `EMFILE`
+    /// (24 on Linux and macOS) to simulate running out of file descriptors.
+    pub fn os_error(count: usize, code: i32) -> Self {
+        Self {
+            count,
+            error: InjectedError::Os(code),
+        }
+    }
+}
+
+/// The faults left to inject, shared between a [`FileAssetReader`] and its clones.
+#[derive(Default)]
+pub(super) struct InjectedFaults(Mutex<Option<FaultSpec>>);
+
+impl InjectedFaults {
+    /// Returns the error to fail the current open with, if any are left.
+    pub(super) fn take(&self) -> Option<Error> {
+        let mut faults = self.0.lock();
+        let spec = faults.as_mut().filter(|spec| spec.count > 0)?;
+        spec.count -= 1;
+        Some(match spec.error {
+            InjectedError::Kind(kind) => Error::new(kind, "injected fault"),
+            InjectedError::Os(code) => Error::from_raw_os_error(code),
+        })
+    }
+}
+
+impl FileAssetReader {
+    /// Makes the next file opens of this reader and its clones fail as described by `spec`,
+    /// replacing any faults still pending. Opens of asset and meta files are affected, directory
+    /// listings are not.
+    ///
+    /// This is meant for testing how an application copes with I/O errors, such as running out
+    /// of file descriptors, and is only available with the `test_util` feature.
+    pub fn inject_fault(&self, spec: FaultSpec) {
+        *self.injected_faults.0.lock() = Some(spec);
+    }
+}
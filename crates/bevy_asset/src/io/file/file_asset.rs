@@ -3,14 +3,38 @@ use crate::io::{
     PathStream, Reader, Writer,
 };
 use async_fs::{read_dir, File};
+use async_io::Timer;
 use futures_io::AsyncSeek;
-use futures_lite::StreamExt;
+use futures_lite::{Stream, StreamExt};
 
+#[cfg(unix)]
+use alloc::format;
 use alloc::{borrow::ToOwned, boxed::Box};
 use core::{pin::Pin, task, task::Poll};
-use std::path::Path;
+#[cfg(unix)]
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::{
+    cap_path_stream,
+    executor::{run_blocking, BlockingFileReader},
+    is_busy_error, join_root, read_stdin,
+    throttle::RateLimitedReader,
+    walk::is_meta_file,
+    watchdog::SlowReadWatchdog,
+    EntryStream, FileAssetReader, FileAssetWriter,
+};
 
-use super::{FileAssetReader, FileAssetWriter};
+/// The `async_fs` version of [`super::create_dir_all_concurrent`].
+async fn create_dir_all_concurrent(path: &Path) -> std::io::Result<()> {
+    match async_fs::create_dir_all(path).await {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists && path.is_dir() => Ok(()),
+        result => result,
+    }
+}
 
 impl AsyncSeekForward for File {
     fn poll_seek_forward(
@@ -33,40 +57,19 @@ impl AsyncSeekForward for File {
 
 impl Reader for File {}
 
-impl AssetReader for FileAssetReader {
-    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
-        let full_path = self.root_path.join(path);
-        File::open(&full_path).await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                AssetReaderError::NotFound(full_path)
-            } else {
-                e.into()
-            }
-        })
-    }
-
-    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
-        let meta_path = get_meta_path(path);
-        let full_path = self.root_path.join(meta_path);
-        File::open(&full_path).await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                AssetReaderError::NotFound(full_path)
-            } else {
-                e.into()
-            }
-        })
-    }
-
-    async fn read_directory<'a>(
-        &'a self,
-        path: &'a Path,
-    ) -> Result<Box<PathStream>, AssetReaderError> {
-        let full_path = self.root_path.join(path);
+impl FileAssetReader {
+    /// Lists every non-meta entry of the directory at `path`, relative to the root path, along
+    /// with any errors encountered while reading individual entries.
+    pub(super) async fn open_directory_entries(
+        &self,
+        path: &Path,
+    ) -> Result<Box<EntryStream>, AssetReaderError> {
+        let root_path = self.current_root();
+        let full_path = join_root(&root_path, path);
         match read_dir(&full_path).await {
             Ok(read_dir) => {
-                let root_path = self.root_path.clone();
-                let mapped_stream = read_dir.filter_map(move |f| {
-                    f.ok().and_then(|dir_entry| {
+                let mapped_stream = read_dir.filter_map(move |f| match f {
+                    Ok(dir_entry) => {
                         let path = dir_entry.path();
                         // filter out meta files as they are not considered assets
                         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
@@ -75,10 +78,11 @@ impl AssetReader for FileAssetReader {
                             }
                         }
                         let relative_path = path.strip_prefix(&root_path).unwrap();
-                        Some(relative_path.to_owned())
-                    })
+                        Some(Ok(relative_path.to_owned()))
+                    }
+                    Err(e) => Some(Err(e.into())),
                 });
-                let read_dir: Box<PathStream> = Box::new(mapped_stream);
+                let read_dir: Box<EntryStream> = Box::new(mapped_stream);
                 Ok(read_dir)
             }
             Err(e) => {
@@ -91,8 +95,202 @@ impl AssetReader for FileAssetReader {
         }
     }
 
+    /// Lists every non-meta entry of the directory at `path`, relative to the root path, along
+    /// with its last modification time.
+    ///
+    /// The modification time is taken from the directory entry, which some platforms can answer
+    /// without a separate `stat` call. Entries that cannot be read, or whose modification time is
+    /// unavailable, are skipped.
+    pub async fn read_directory_with_mtime(
+        &self,
+        path: &Path,
+    ) -> Result<impl Stream<Item = (PathBuf, SystemTime)> + Unpin + Send, AssetReaderError> {
+        let root_path = self.current_root();
+        let full_path = join_root(&root_path, path);
+        let read_dir = match read_dir(&full_path).await {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(AssetReaderError::NotFound(full_path));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let stream = read_dir
+            .then(move |entry| {
+                let root_path = root_path.clone();
+                async move {
+                    let dir_entry = entry.ok()?;
+                    let path = dir_entry.path();
+                    if is_meta_file(&path) {
+                        return None;
+                    }
+                    let modified = dir_entry.metadata().await.ok()?.modified().ok()?;
+                    let relative_path = path.strip_prefix(&root_path).unwrap();
+                    Some((relative_path.to_owned(), modified))
+                }
+            })
+            .filter_map(core::convert::identity);
+        Ok(Box::pin(stream))
+    }
+
+    /// Opens the file at `full_path` for reading. With a [`BlockingExecutor`] the file is opened
+    /// and read through it, otherwise it is opened with the configured share mode on Windows.
+    ///
+    /// [`BlockingExecutor`]: super::BlockingExecutor
+    async fn open_file(&self, full_path: &Path) -> std::io::Result<Box<dyn Reader>> {
+        #[cfg(feature = "test_util")]
+        if let Some(error) = self.injected_faults.take() {
+            return Err(error);
+        }
+        if let Some(executor) = &self.blocking_executor {
+            let full_path = full_path.to_owned();
+            // a task dropped by the executor fails this read only, the reader is only shut down
+            // when asked to
+            let file = run_blocking(&**executor, move || std::fs::File::open(full_path)).await??;
+            return Ok(Box::new(BlockingFileReader::new(executor.clone(), file)));
+        }
+        let mut options = async_fs::OpenOptions::new();
+        options.read(true);
+        #[cfg(windows)]
+        async_fs::windows::OpenOptionsExt::share_mode(&mut options, self.read_share_mode.flags());
+        Ok(Box::new(options.open(full_path).await?))
+    }
+
+    /// Applies the configured rate limit, decompression and slow read watchdog to an opened asset
+    /// file.
+    async fn wrap_reader(
+        &self,
+        full_path: PathBuf,
+        reader: Box<dyn Reader>,
+    ) -> Result<Box<dyn Reader>, AssetReaderError> {
+        let reader: Box<dyn Reader> = match &self.read_rate_limit {
+            Some(limit) => Box::new(RateLimitedReader::new(reader, limit.clone())),
+            None => reader,
+        };
+        #[cfg(feature = "compression")]
+        let reader: Box<dyn Reader> = if self.auto_decompress {
+            Box::new(super::DecompressReader::new(reader).await?)
+        } else {
+            reader
+        };
+        match self.slow_read_warn_threshold {
+            Some(threshold) => Ok(Box::new(SlowReadWatchdog::new(
+                reader, full_path, threshold,
+            ))),
+            None => Ok(reader),
+        }
+    }
+
+    /// Reads an asset from `fd`, a file that was already opened elsewhere, for example by a sandbox
+    /// broker that does not allow opening files by path. The configured wrappers are applied as
+    /// for [`AssetReader::read`], and `fd` is closed when the returned reader is dropped.
+    ///
+    /// A raw descriptor can be turned into an [`OwnedFd`] with
+    /// [`FromRawFd::from_raw_fd`](std::os::fd::FromRawFd::from_raw_fd).
+    #[cfg(unix)]
+    pub async fn read_from_fd(&self, fd: OwnedFd) -> Result<Box<dyn Reader>, AssetReaderError> {
+        let name = PathBuf::from(format!("<fd {}>", fd.as_raw_fd()));
+        let file: Box<dyn Reader> = Box::new(File::from(std::fs::File::from(fd)));
+        self.wrap_reader(name, file).await
+    }
+
+    /// Opens the asset file at `full_path` and applies the configured wrappers, without retrying.
+    pub(super) async fn open_reader(
+        &self,
+        full_path: PathBuf,
+    ) -> Result<Box<dyn Reader>, AssetReaderError> {
+        match self.open_file(&full_path).await {
+            Ok(reader) => self.wrap_reader(full_path, reader).await,
+            Err(e) => Err(self.open_error(full_path, e).await),
+        }
+    }
+
+    /// Opens the asset at `path`, honoring the stdin path and the not found retry. This is
+    /// [`AssetReader::read`] with a reader that does not borrow `self`.
+    pub(super) async fn read_asset(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Reader>, AssetReaderError> {
+        if self.is_stdin_path(path) {
+            // stdin has no async interface, so it is read on a thread that is allowed to block
+            let reader = match &self.blocking_executor {
+                // the only way running it fails is the executor dropping it
+                Some(executor) => run_blocking(&**executor, read_stdin)
+                    .await
+                    .map_err(|_| AssetReaderError::Shutdown)?,
+                None => blocking::unblock(read_stdin).await,
+            };
+            return Ok(Box::new(reader?));
+        }
+        let mut full_path = self.full_path(path);
+        let mut attempt = 0;
+        let mut busy_attempt = 0;
+        loop {
+            if self.is_shut_down() {
+                return Err(AssetReaderError::Shutdown);
+            }
+            match self.open_file(&full_path).await {
+                Ok(reader) => return self.wrap_reader(full_path, reader).await,
+                // the root path may have moved, in which case the read is retried at the new one
+                Err(_) if self.reresolve_root() => full_path = self.full_path(path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => match self.not_found_retry {
+                    // the file may be mid-way through an atomic rename, so give the writer a
+                    // chance to finish before reporting it as missing
+                    Some(retry) if attempt < retry.attempts => {
+                        attempt += 1;
+                        Timer::after(retry.delay).await;
+                    }
+                    _ => return Err(self.open_error(full_path, e).await),
+                },
+                // an editor may be saving the file, which usually takes a moment at most
+                Err(e) if is_busy_error(&e) => match self.busy_retry {
+                    Some(retry) if busy_attempt < retry.attempts => {
+                        busy_attempt += 1;
+                        Timer::after(retry.delay).await;
+                    }
+                    _ => return Err(self.open_error(full_path, e).await),
+                },
+                Err(e) => return Err(self.open_error(full_path, e).await),
+            }
+        }
+    }
+}
+
+impl AssetReader for FileAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.read_asset(path).await
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let meta_path = get_meta_path(path);
+        let mut full_path = self.full_path(&meta_path);
+        if self.is_stdin_path(path) {
+            return Err(AssetReaderError::NotFound(full_path));
+        }
+        if self.is_shut_down() {
+            return Err(AssetReaderError::Shutdown);
+        }
+        let mut result = self.open_file(&full_path).await;
+        if result.is_err() && self.reresolve_root() {
+            full_path = self.full_path(&meta_path);
+            result = self.open_file(&full_path).await;
+        }
+        match result {
+            Ok(reader) => Ok(reader),
+            Err(e) => Err(self.open_error(full_path, e).await),
+        }
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let entries = self.open_directory_entries(path).await?;
+        let read_dir: Box<PathStream> = Box::new(entries.filter_map(Result::ok));
+        Ok(cap_path_stream(read_dir, path, self.max_directory_entries))
+    }
+
     async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         let metadata = full_path
             .metadata()
             .map_err(|_e| AssetReaderError::NotFound(path.to_owned()))?;
@@ -100,37 +298,64 @@ impl AssetReader for FileAssetReader {
     }
 }
 
+impl FileAssetWriter {
+    /// Creates the file at `path` and returns a writer for it, failing with
+    /// [`AssetWriterError::AlreadyExists`] if it is already present. Checking for the file and
+    /// creating it is a single atomic operation, so this can be used as a lock or marker file to
+    /// coordinate processes sharing the asset directory.
+    pub async fn create_exclusive(&self, path: &Path) -> Result<Box<Writer>, AssetWriterError> {
+        let full_path = self.full_path(path);
+        if let Some(parent) = full_path.parent() {
+            create_dir_all_concurrent(parent).await?;
+        }
+        let file = match async_fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&full_path)
+            .await
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(AssetWriterError::AlreadyExists(full_path));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let writer: Box<Writer> = Box::new(file);
+        Ok(self.wrap_writer(path, writer))
+    }
+}
+
 impl AssetWriter for FileAssetWriter {
     async fn write<'a>(&'a self, path: &'a Path) -> Result<Box<Writer>, AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         if let Some(parent) = full_path.parent() {
-            async_fs::create_dir_all(parent).await?;
+            create_dir_all_concurrent(parent).await?;
         }
         let file = File::create(&full_path).await?;
         let writer: Box<Writer> = Box::new(file);
-        Ok(writer)
+        Ok(self.wrap_writer(path, writer))
     }
 
     async fn write_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Writer>, AssetWriterError> {
         let meta_path = get_meta_path(path);
-        let full_path = self.root_path.join(meta_path);
+        let full_path = self.full_path(&meta_path);
         if let Some(parent) = full_path.parent() {
-            async_fs::create_dir_all(parent).await?;
+            create_dir_all_concurrent(parent).await?;
         }
         let file = File::create(&full_path).await?;
         let writer: Box<Writer> = Box::new(file);
-        Ok(writer)
+        Ok(self.wrap_writer(&meta_path, writer))
     }
 
     async fn remove<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         async_fs::remove_file(full_path).await?;
         Ok(())
     }
 
     async fn remove_meta<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
         let meta_path = get_meta_path(path);
-        let full_path = self.root_path.join(meta_path);
+        let full_path = self.full_path(&meta_path);
         async_fs::remove_file(full_path).await?;
         Ok(())
     }
@@ -140,10 +365,10 @@ impl AssetWriter for FileAssetWriter {
         old_path: &'a Path,
         new_path: &'a Path,
     ) -> Result<(), AssetWriterError> {
-        let full_old_path = self.root_path.join(old_path);
-        let full_new_path = self.root_path.join(new_path);
+        let full_old_path = self.full_path(old_path);
+        let full_new_path = self.full_path(new_path);
         if let Some(parent) = full_new_path.parent() {
-            async_fs::create_dir_all(parent).await?;
+            create_dir_all_concurrent(parent).await?;
         }
         async_fs::rename(full_old_path, full_new_path).await?;
         Ok(())
@@ -156,29 +381,29 @@ impl AssetWriter for FileAssetWriter {
     ) -> Result<(), AssetWriterError> {
         let old_meta_path = get_meta_path(old_path);
         let new_meta_path = get_meta_path(new_path);
-        let full_old_path = self.root_path.join(old_meta_path);
-        let full_new_path = self.root_path.join(new_meta_path);
+        let full_old_path = self.full_path(&old_meta_path);
+        let full_new_path = self.full_path(&new_meta_path);
         if let Some(parent) = full_new_path.parent() {
-            async_fs::create_dir_all(parent).await?;
+            create_dir_all_concurrent(parent).await?;
         }
         async_fs::rename(full_old_path, full_new_path).await?;
         Ok(())
     }
 
     async fn create_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
-        async_fs::create_dir_all(full_path).await?;
+        let full_path = self.full_path(path);
+        create_dir_all_concurrent(&full_path).await?;
         Ok(())
     }
 
     async fn remove_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         async_fs::remove_dir_all(full_path).await?;
         Ok(())
     }
 
     async fn remove_empty_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         async_fs::remove_dir(full_path).await?;
         Ok(())
     }
@@ -187,9 +412,9 @@ impl AssetWriter for FileAssetWriter {
         &'a self,
         path: &'a Path,
     ) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         async_fs::remove_dir_all(&full_path).await?;
-        async_fs::create_dir_all(&full_path).await?;
+        create_dir_all_concurrent(&full_path).await?;
         Ok(())
     }
 }
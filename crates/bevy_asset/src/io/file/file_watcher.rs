@@ -1,8 +1,10 @@
+use super::walk;
 use crate::{
-    io::{AssetSourceEvent, AssetWatcher},
+    io::{AssetReaderError, AssetSourceEvent, AssetWatcher},
     path::normalize_path,
+    SharedDescriptorLimit,
 };
-use alloc::borrow::ToOwned;
+use alloc::{borrow::ToOwned, vec, vec::Vec};
 use core::time::Duration;
 use crossbeam_channel::Sender;
 use notify_debouncer_full::{
@@ -14,7 +16,10 @@ use notify_debouncer_full::{
     },
     DebounceEventResult, Debouncer, RecommendedCache,
 };
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
 use tracing::error;
 
 /// An [`AssetWatcher`] that watches the filesystem for changes to asset files in a given root folder and emits [`AssetSourceEvent`]
@@ -26,6 +31,8 @@ use tracing::error;
 /// on some systems to avoid processing a change event before it has actually been applied.
 pub struct FileWatcher {
     _watcher: Debouncer<RecommendedWatcher, RecommendedCache>,
+    watched_paths: Vec<PathBuf>,
+    sender: Sender<AssetSourceEvent>,
 }
 
 impl FileWatcher {
@@ -34,6 +41,30 @@ impl FileWatcher {
         path: PathBuf,
         sender: Sender<AssetSourceEvent>,
         debounce_wait_time: Duration,
+    ) -> Result<Self, notify::Error> {
+        Self::create(path, sender, debounce_wait_time, None)
+    }
+
+    /// Creates a new [`FileWatcher`] like [`FileWatcher::new`], that additionally drops any event
+    /// identical to one it emitted within the last `dedup_window`.
+    ///
+    /// Some editors write a file several times for a single save, which debouncing alone can
+    /// split into separate batches of events, each triggering a reload. The first event is still
+    /// emitted as soon as it is debounced, only its repeats within the window are dropped.
+    pub fn new_with_dedup_window(
+        path: PathBuf,
+        sender: Sender<AssetSourceEvent>,
+        debounce_wait_time: Duration,
+        dedup_window: Duration,
+    ) -> Result<Self, notify::Error> {
+        Self::create(path, sender, debounce_wait_time, Some(dedup_window))
+    }
+
+    fn create(
+        path: PathBuf,
+        sender: Sender<AssetSourceEvent>,
+        debounce_wait_time: Duration,
+        dedup_window: Option<Duration>,
     ) -> Result<Self, notify::Error> {
         let root = normalize_path(&path).canonicalize().unwrap();
         let watcher = new_asset_event_debouncer(
@@ -41,11 +72,74 @@ impl FileWatcher {
             debounce_wait_time,
             FileEventHandler {
                 root,
-                sender,
+                sender: sender.clone(),
                 last_event: None,
+                dedup_window,
+                recent_events: Vec::new(),
             },
         )?;
-        Ok(FileWatcher { _watcher: watcher })
+        Ok(FileWatcher {
+            _watcher: watcher,
+            // asset sources already resolve the path against the base path
+            watched_paths: vec![path],
+            sender,
+        })
+    }
+
+    /// Returns the directories and files currently registered with the underlying `notify` backend.
+    ///
+    /// Every returned path is watched recursively.
+    pub fn watched_paths(&self) -> Vec<PathBuf> {
+        self.watched_paths.clone()
+    }
+
+    /// Returns the number of [`AssetSourceEvent`]s this watcher has emitted that have not been
+    /// received yet.
+    ///
+    /// A count that keeps growing means events are produced faster than they are handled, which
+    /// shows up as hot-reload latency. Events still held back by debouncing are not included.
+    pub fn pending_event_count(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Walks the watched directories and emits an [`AssetSourceEvent::AddedAsset`] for every
+    /// existing asset, so that initial assets can be handled by the same code as later changes.
+    /// Returns the number of events emitted. Meta files are skipped.
+    ///
+    /// The walk is performed with blocking filesystem calls, and it stops early if the receiving
+    /// end of the event channel has been dropped. It opens one directory at a time; use
+    /// [`FileWatcher::emit_initial_scan_within`] to also count that directory against a
+    /// descriptor limit.
+    pub fn emit_initial_scan(&self) -> Result<usize, AssetReaderError> {
+        self.scan(None)
+    }
+
+    /// Like [`FileWatcher::emit_initial_scan`], but holds a permit of `limit` while each
+    /// directory is open, blocking until one is available. Passing the
+    /// [`AssetServer::shared_descriptor_limit`](crate::AssetServer::shared_descriptor_limit) keeps
+    /// the scan within the budget of the server's loads.
+    pub fn emit_initial_scan_within(
+        &self,
+        limit: &SharedDescriptorLimit,
+    ) -> Result<usize, AssetReaderError> {
+        self.scan(Some(limit))
+    }
+
+    fn scan(&self, limit: Option<&SharedDescriptorLimit>) -> Result<usize, AssetReaderError> {
+        let mut emitted = 0;
+        for watched_path in &self.watched_paths {
+            for file in walk::walk_files_within(watched_path, Path::new(""), false, limit)? {
+                if self
+                    .sender
+                    .send(AssetSourceEvent::AddedAsset(file.path))
+                    .is_err()
+                {
+                    return Ok(emitted);
+                }
+                emitted += 1;
+            }
+        }
+        Ok(emitted)
     }
 }
 
@@ -255,6 +349,9 @@ pub(crate) struct FileEventHandler {
     sender: Sender<AssetSourceEvent>,
     root: PathBuf,
     last_event: Option<AssetSourceEvent>,
+    dedup_window: Option<Duration>,
+    /// Events emitted within the last `dedup_window`, with the time they were emitted.
+    recent_events: Vec<(AssetSourceEvent, Instant)>,
 }
 
 impl FilesystemEventHandler for FileEventHandler {
@@ -267,10 +364,24 @@ impl FilesystemEventHandler for FileEventHandler {
     }
 
     fn handle(&mut self, _absolute_paths: &[PathBuf], event: AssetSourceEvent) {
-        if self.last_event.as_ref() != Some(&event) {
-            self.last_event = Some(event.clone());
-            self.sender.send(event).unwrap();
+        if self.last_event.as_ref() == Some(&event) {
+            return;
+        }
+        if let Some(dedup_window) = self.dedup_window {
+            let now = Instant::now();
+            self.recent_events
+                .retain(|(_, emitted_at)| now.duration_since(*emitted_at) < dedup_window);
+            if self
+                .recent_events
+                .iter()
+                .any(|(recent, _)| *recent == event)
+            {
+                return;
+            }
+            self.recent_events.push((event.clone(), now));
         }
+        self.last_event = Some(event.clone());
+        self.sender.send(event).unwrap();
     }
 }
 
@@ -283,3 +394,80 @@ pub(crate) trait FilesystemEventHandler: Send + Sync + 'static {
     /// Handle the given event
     fn handle(&mut self, absolute_paths: &[PathBuf], event: AssetSourceEvent);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::file::tests::TempDir;
+    use std::thread;
+
+    #[test]
+    fn initial_scan_waits_for_a_descriptor_permit() {
+        let root = TempDir::new("scan");
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        std::fs::write(root.join("nested/b.txt"), b"b").unwrap();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let watcher =
+            FileWatcher::new(root.to_path_buf(), sender, Duration::from_millis(300)).unwrap();
+        let limit = SharedDescriptorLimit::new(1);
+        let reservation = limit.limit.try_reserve().unwrap();
+        let (emitted, waited) = thread::scope(|scope| {
+            let scan = scope.spawn(|| watcher.emit_initial_scan_within(&limit));
+            thread::sleep(Duration::from_millis(100));
+            let waited = receiver.is_empty() && !scan.is_finished();
+            drop(reservation);
+            (scan.join().unwrap(), waited)
+        });
+        drop(watcher);
+        assert!(waited);
+        assert_eq!(emitted.unwrap(), 2);
+        assert_eq!(limit.available(), 1);
+    }
+
+    #[test]
+    fn relative_roots_are_watched_as_given() {
+        // relative to the working directory, which is where the scan resolves it from
+        let root = TempDir::in_dir(Path::new(""), "watch");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let watcher =
+            FileWatcher::new(root.to_path_buf(), sender, Duration::from_millis(300)).unwrap();
+        let watched = watcher.watched_paths();
+        let emitted = watcher.emit_initial_scan();
+        drop(watcher);
+        assert_eq!(watched, [root.to_path_buf()]);
+        assert_eq!(emitted.unwrap(), 1);
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            AssetSourceEvent::AddedAsset(PathBuf::from("a.txt"))
+        );
+    }
+
+    #[test]
+    fn repeated_events_are_dropped_within_the_dedup_window() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let mut handler = FileEventHandler {
+            sender,
+            root: PathBuf::new(),
+            last_event: None,
+            dedup_window: Some(Duration::from_millis(200)),
+            recent_events: Vec::new(),
+        };
+        let modified = |path: &str| AssetSourceEvent::ModifiedAsset(PathBuf::from(path));
+        // each batch of debounced events begins anew, so only the window catches the repeats
+        for path in ["a.txt", "a.txt", "b.txt"] {
+            handler.begin();
+            handler.handle(&[], modified(path));
+        }
+        thread::sleep(Duration::from_millis(250));
+        handler.begin();
+        handler.handle(&[], modified("a.txt"));
+        let emitted: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(
+            emitted,
+            [modified("a.txt"), modified("b.txt"), modified("a.txt")]
+        );
+    }
+}
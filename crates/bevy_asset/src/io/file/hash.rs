@@ -0,0 +1,146 @@
+use super::{FileAssetReader, FileAssetWriter};
+use crate::io::{AssetReaderError, AssetWriterError};
+use alloc::vec::Vec;
+use std::{
+    fs::File,
+    io::{ErrorKind, Read},
+    path::Path,
+};
+
+/// An algorithm used to hash the contents of asset files.
+///
+/// [`Fnv1aHasher`] is the default, being fast and dependency free. [`Blake3Hasher`] is available
+/// when collisions must be resistant to tampering, and `crc32` and `xxhash` implementations are
+/// available behind the features of the same names. Implement this trait to plug in any other
+/// algorithm.
+pub trait AssetHasher {
+    /// Feeds `bytes` into the hash.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Consumes the hasher, returning the hash of every byte fed to it.
+    fn finalize(self) -> Vec<u8>;
+}
+
+/// The hasher used when no other is chosen.
+pub type DefaultAssetHasher = Fnv1aHasher;
+
+/// The 64 bit FNV-1a hash, a fast non-cryptographic hash.
+#[derive(Clone, Copy, Debug)]
+pub struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl AssetHasher for Fnv1aHasher {
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+/// The BLAKE3 cryptographic hash.
+#[derive(Clone, Default)]
+pub struct Blake3Hasher(blake3::Hasher);
+
+impl AssetHasher for Blake3Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize().as_bytes().to_vec()
+    }
+}
+
+/// The CRC-32 checksum, the fastest option but only suited to detecting accidental corruption.
+#[cfg(feature = "crc32")]
+#[derive(Clone, Default)]
+pub struct Crc32Hasher(crc32fast::Hasher);
+
+#[cfg(feature = "crc32")]
+impl AssetHasher for Crc32Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.finalize().to_be_bytes().to_vec()
+    }
+}
+
+/// The 64 bit XXH3 hash, a fast non-cryptographic hash with good distribution.
+#[cfg(feature = "xxhash")]
+#[derive(Clone, Default)]
+pub struct Xxh3Hasher(xxhash_rust::xxh3::Xxh3);
+
+#[cfg(feature = "xxhash")]
+impl AssetHasher for Xxh3Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        self.0.digest().to_be_bytes().to_vec()
+    }
+}
+
+impl FileAssetReader {
+    /// Hashes the contents of the asset at `path` with `hasher`, returning the hash.
+    ///
+    /// The file is streamed through the hasher with blocking filesystem calls, so it is never
+    /// held in memory in full.
+    pub fn hash_asset<H: AssetHasher>(
+        &self,
+        path: &Path,
+        hasher: H,
+    ) -> Result<Vec<u8>, AssetReaderError> {
+        let full_path = self.full_path(path);
+        let mut file =
+            File::open(&full_path).map_err(|e| self.open_error_blocking(full_path, e))?;
+        Ok(hash_file(&mut file, hasher)?)
+    }
+}
+
+impl FileAssetWriter {
+    /// Removes the asset at `path` only if hashing its contents with `hasher` gives `expected`,
+    /// returning [`AssetWriterError::Conflict`] otherwise. This avoids removing a newer version
+    /// written by someone else since the expected hash was taken.
+    ///
+    /// The file is hashed with blocking filesystem calls. Checking the hash and removing the file
+    /// are separate operations, so a write landing in between is still removed.
+    pub fn remove_if_matches<H: AssetHasher>(
+        &self,
+        path: &Path,
+        hasher: H,
+        expected: &[u8],
+    ) -> Result<(), AssetWriterError> {
+        let full_path = self.full_path(path);
+        let hash = hash_file(&mut File::open(&full_path)?, hasher)?;
+        if hash != expected {
+            return Err(AssetWriterError::Conflict(full_path));
+        }
+        std::fs::remove_file(&full_path)?;
+        Ok(())
+    }
+}
+
+/// Streams the contents of `file` through `hasher`, returning the hash.
+fn hash_file<H: AssetHasher>(file: &mut File, mut hasher: H) -> std::io::Result<Vec<u8>> {
+    let mut buffer = [0; 8 * 1024];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(hasher.finalize()),
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
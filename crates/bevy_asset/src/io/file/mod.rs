@@ -1,52 +1,413 @@
+mod concat;
+mod config;
+#[cfg(feature = "compression")]
+mod decompress;
+#[cfg(feature = "multi_threaded")]
+mod executor;
+#[cfg(feature = "test_util")]
+mod fault;
 #[cfg(feature = "file_watcher")]
 mod file_watcher;
+mod hash;
+mod record;
+mod search;
+mod staging;
+#[cfg(feature = "multi_threaded")]
+mod throttle;
+mod transform;
+mod tree;
+mod walk;
+#[cfg(feature = "multi_threaded")]
+mod watchdog;
 
 #[cfg(feature = "multi_threaded")]
 mod file_asset;
 #[cfg(not(feature = "multi_threaded"))]
 mod sync_file_asset;
 
+pub use config::*;
+#[cfg(feature = "compression")]
+pub use decompress::*;
+#[cfg(feature = "multi_threaded")]
+pub use executor::BlockingExecutor;
+#[cfg(feature = "test_util")]
+pub use fault::FaultSpec;
 #[cfg(feature = "file_watcher")]
 pub use file_watcher::*;
-use tracing::{debug, error};
+pub use hash::*;
+pub use record::*;
+pub use search::SearchPathAssetReader;
+pub use staging::StagingAssetWriter;
+use tracing::{debug, error, warn};
+pub use transform::WriteTransform;
+pub use tree::*;
 
-use alloc::borrow::ToOwned;
+use crate::io::{
+    get_meta_path, AssetReader, AssetReaderError, AssetWriter, AssetWriterError, PathStream,
+    Reader, VecReader, Writer,
+};
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use alloc::{boxed::Box, sync::Arc};
+use bevy_platform_support::collections::HashMap;
+use core::{
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures_io::AsyncRead;
+use futures_lite::{io::BufWriter, ready, AsyncReadExt, Stream, StreamExt};
+use parking_lot::RwLock;
 use std::{
     env,
+    io::{ErrorKind, Read, Write},
     path::{Path, PathBuf},
 };
 
+/// The strategy that resolved the base path returned by [`FileAssetReader::get_base_path`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BasePathSource {
+    /// The `BEVY_ASSET_ROOT` environment variable.
+    AssetRootEnv,
+    /// The `CARGO_MANIFEST_DIR` environment variable, set when running through cargo.
+    CargoManifestDir,
+    /// The directory containing the running executable.
+    ExecutableDir,
+}
+
+impl BasePathSource {
+    /// The order in which the strategies are tried by [`FileAssetReader::get_base_path`].
+    pub const DEFAULT_ORDER: [BasePathSource; 3] = [
+        BasePathSource::AssetRootEnv,
+        BasePathSource::CargoManifestDir,
+        BasePathSource::ExecutableDir,
+    ];
+
+    /// Returns the base path this strategy yields, or `None` if it does not apply, for example
+    /// because its environment variable is not set.
+    pub fn base_path(self) -> Option<PathBuf> {
+        match self {
+            BasePathSource::AssetRootEnv => env::var("BEVY_ASSET_ROOT").ok().map(PathBuf::from),
+            BasePathSource::CargoManifestDir => {
+                env::var("CARGO_MANIFEST_DIR").ok().map(PathBuf::from)
+            }
+            BasePathSource::ExecutableDir => env::current_exe()
+                .ok()
+                .and_then(|path| path.parent().map(ToOwned::to_owned)),
+        }
+    }
+}
+
 pub(crate) fn get_base_path() -> PathBuf {
-    if let Ok(manifest_dir) = env::var("BEVY_ASSET_ROOT") {
-        PathBuf::from(manifest_dir)
-    } else if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
-        PathBuf::from(manifest_dir)
-    } else {
-        env::current_exe()
-            .map(|path| path.parent().map(ToOwned::to_owned).unwrap())
-            .unwrap()
+    get_base_path_with_source().0
+}
+
+pub(crate) fn get_base_path_with_source() -> (PathBuf, BasePathSource) {
+    BasePathSource::DEFAULT_ORDER
+        .into_iter()
+        .find_map(|source| Some((source.base_path()?, source)))
+        .expect("the directory of the executable could not be found")
+}
+
+/// Returns the base path yielded by the first strategy in `order` that yields an existing
+/// directory, along with that strategy.
+fn get_base_path_in_order(order: &[BasePathSource]) -> Option<(PathBuf, BasePathSource)> {
+    order.iter().find_map(|&source| {
+        let base_path = source.base_path().filter(|path| path.is_dir())?;
+        Some((base_path, source))
+    })
+}
+
+/// Joins `path` onto the base path, returning the result along with the strategy that resolved
+/// the base path. An absolute `path` replaces the base path entirely, which is worth calling out
+/// when `BEVY_ASSET_ROOT` was explicitly set.
+///
+/// With an `order`, the base path is resolved by [`get_base_path_in_order`], falling back to the
+/// default resolution if none of its strategies yields an existing directory.
+fn resolve_root_path(
+    path: &Path,
+    order: Option<&[BasePathSource]>,
+) -> (PathBuf, Option<BasePathSource>) {
+    if path.is_absolute() {
+        if env::var_os("BEVY_ASSET_ROOT").is_some() {
+            warn!(
+                "Asset path {} is absolute, so BEVY_ASSET_ROOT is ignored for it.",
+                path.display()
+            );
+        }
+        return (path.to_owned(), None);
+    }
+    let (base_path, source) = match order {
+        Some(order) => get_base_path_in_order(order).unwrap_or_else(|| {
+            warn!(
+                "None of the base path strategies {order:?} yields an existing directory, falling back to the default order."
+            );
+            get_base_path_with_source()
+        }),
+        None => get_base_path_with_source(),
+    };
+    (base_path.join(path), Some(source))
+}
+
+/// Configures how many times (and how often) a failed file operation is retried before the
+/// error is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadRetry {
+    /// The number of additional attempts made after the first failure.
+    pub attempts: u32,
+    /// How long to wait between attempts.
+    pub delay: Duration,
+}
+
+/// The retry applied by default when an asset file is busy, see
+/// [`FileAssetReader::with_busy_retry`].
+const DEFAULT_BUSY_RETRY: ReadRetry = ReadRetry {
+    attempts: 2,
+    delay: Duration::from_millis(10),
+};
+
+/// Which other accesses to a file are allowed while a [`FileAssetReader`] has it open, on Windows.
+///
+/// Other platforms do not lock files opened for reading, so this has no equivalent there.
+#[cfg(windows)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadShareMode {
+    /// Other processes may read, write, delete and rename the file. This lets editors save over
+    /// an asset while it is being read, which hot reloading relies on.
+    #[default]
+    ReadWriteDelete,
+    /// Other processes may read and write the file, but not delete or rename it. This also blocks
+    /// editors that save by renaming a temporary file over the asset.
+    ReadWrite,
+    /// Other processes may only read the file.
+    Read,
+}
+
+#[cfg(windows)]
+impl ReadShareMode {
+    /// Returns the `FILE_SHARE_*` flags for this mode.
+    fn flags(self) -> u32 {
+        const FILE_SHARE_READ: u32 = 0x1;
+        const FILE_SHARE_WRITE: u32 = 0x2;
+        const FILE_SHARE_DELETE: u32 = 0x4;
+        match self {
+            ReadShareMode::ReadWriteDelete => {
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE
+            }
+            ReadShareMode::ReadWrite => FILE_SHARE_READ | FILE_SHARE_WRITE,
+            ReadShareMode::Read => FILE_SHARE_READ,
+        }
     }
 }
 
 /// I/O implementation for the local filesystem.
 ///
 /// This asset I/O is fully featured but it's not available on `android` and `wasm` targets.
+#[derive(Clone)]
 pub struct FileAssetReader {
     root_path: PathBuf,
+    not_found_retry: Option<ReadRetry>,
+    busy_retry: Option<ReadRetry>,
+    stdin_path: Option<PathBuf>,
+    max_directory_entries: Option<usize>,
+    dangling_symlink_errors: bool,
+    #[cfg(feature = "compression")]
+    auto_decompress: bool,
+    #[cfg(feature = "multi_threaded")]
+    slow_read_warn_threshold: Option<Duration>,
+    #[cfg(feature = "multi_threaded")]
+    read_rate_limit: Option<Arc<throttle::ReadRateLimit>>,
+    #[cfg(windows)]
+    read_share_mode: ReadShareMode,
+    #[cfg(feature = "multi_threaded")]
+    blocking_executor: Option<Arc<dyn BlockingExecutor>>,
+    shutdown: Arc<AtomicBool>,
+    root_resolver: Option<Arc<RootResolver>>,
+    resolved_root: Arc<RwLock<Option<PathBuf>>>,
+    #[cfg(feature = "test_util")]
+    injected_faults: Arc<fault::InjectedFaults>,
 }
 
+/// A function that finds the new location of the root path of a [`FileAssetReader`] after it
+/// stopped being a directory, for example because its volume was remounted elsewhere. It receives
+/// the current root path, and returns the new one if it could be found.
+pub type RootResolver = dyn Fn(&Path) -> Option<PathBuf> + Send + Sync;
+
 impl FileAssetReader {
     /// Creates a new `FileAssetIo` at a path relative to the executable's directory, optionally
     /// watching for changes.
     ///
+    /// If `path` is absolute it is used as-is and the base path is ignored.
+    ///
     /// See `get_base_path` below.
     pub fn new<P: AsRef<Path>>(path: P) -> Self {
-        let root_path = Self::get_base_path().join(path.as_ref());
-        debug!(
-            "Asset Server using {} as its base path.",
-            root_path.display()
-        );
-        Self { root_path }
+        Self::from_resolved_root(resolve_root_path(path.as_ref(), None))
+    }
+
+    /// Creates a new [`FileAssetReader`] like [`FileAssetReader::new`], but resolving the base
+    /// path with the first strategy in `order` that yields an existing directory. If none does,
+    /// the base path is resolved as usual.
+    ///
+    /// For example, a release build can prefer the executable's directory over
+    /// `CARGO_MANIFEST_DIR` by passing `[ExecutableDir, AssetRootEnv]`.
+    pub fn new_with_base_path_order<P: AsRef<Path>>(path: P, order: &[BasePathSource]) -> Self {
+        Self::from_resolved_root(resolve_root_path(path.as_ref(), Some(order)))
+    }
+
+    fn from_resolved_root((root_path, source): (PathBuf, Option<BasePathSource>)) -> Self {
+        match source {
+            Some(source) => debug!(
+                "Asset Server using {} as its base path, resolved from {source:?}.",
+                root_path.display()
+            ),
+            None => debug!(
+                "Asset Server using {} as its base path.",
+                root_path.display()
+            ),
+        }
+        Self {
+            root_path,
+            not_found_retry: None,
+            busy_retry: Some(DEFAULT_BUSY_RETRY),
+            stdin_path: None,
+            max_directory_entries: None,
+            dangling_symlink_errors: false,
+            #[cfg(feature = "compression")]
+            auto_decompress: false,
+            #[cfg(feature = "multi_threaded")]
+            slow_read_warn_threshold: None,
+            #[cfg(feature = "multi_threaded")]
+            read_rate_limit: None,
+            #[cfg(windows)]
+            read_share_mode: ReadShareMode::default(),
+            #[cfg(feature = "multi_threaded")]
+            blocking_executor: None,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            root_resolver: None,
+            resolved_root: Arc::new(RwLock::new(None)),
+            #[cfg(feature = "test_util")]
+            injected_faults: Arc::default(),
+        }
+    }
+
+    /// Retries [`AssetReader::read`](crate::io::AssetReader::read) according to `retry` when the
+    /// file is not found, instead of failing immediately.
+    ///
+    /// Writers that save atomically (write to a temporary file, then rename it over the
+    /// destination) can leave a brief window where the path does not exist. Enabling this keeps
+    /// concurrent readers from spuriously failing with [`AssetReaderError::NotFound`] during
+    /// such a swap, at the cost of delaying the error for assets that are genuinely missing.
+    ///
+    /// This does not apply to meta files, which are routinely absent.
+    ///
+    /// [`AssetReaderError::NotFound`]: crate::io::AssetReaderError::NotFound
+    pub fn with_not_found_retry(mut self, retry: ReadRetry) -> Self {
+        self.not_found_retry = Some(retry);
+        self
+    }
+
+    /// Retries [`AssetReader::read`](crate::io::AssetReader::read) according to `retry` when the
+    /// file is busy because another process is using it, such as a sharing violation on Windows
+    /// or `ETXTBSY` on Unix. This smooths over an editor saving an asset while it is being
+    /// reloaded. Defaults to two retries 10 milliseconds apart, pass `None` to fail immediately.
+    ///
+    /// This does not apply to meta files.
+    pub fn with_busy_retry(mut self, retry: Option<ReadRetry>) -> Self {
+        self.busy_retry = retry;
+        self
+    }
+
+    /// Serves reads of the asset at `path` (for example `-`) from the process's standard input
+    /// instead of the filesystem.
+    ///
+    /// This allows piping asset bytes into command-line tooling without a temporary file. Stdin
+    /// is read to the end when the asset is opened, so it can only be loaded once. The sentinel
+    /// asset never has a meta file, and its extension (if any) is still used to select a loader.
+    pub fn with_stdin_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.stdin_path = Some(path.into());
+        self
+    }
+
+    fn is_stdin_path(&self, path: &Path) -> bool {
+        self.stdin_path.as_deref() == Some(path)
+    }
+
+    /// Joins `path` onto the root path. A leading separator is ignored, so that web-style paths
+    /// such as `/models/foo.gltf` resolve under the root path instead of replacing it.
+    fn full_path(&self, path: &Path) -> PathBuf {
+        join_root(&self.current_root(), path)
+    }
+
+    /// Re-resolves the root path with `resolver` when a read fails because the root path is no
+    /// longer a directory, for example because the volume holding the assets was remounted at a
+    /// new path, then retries the read once. Every later operation uses the new root path, which
+    /// is returned by [`FileAssetReader::current_root`]. This is off by default, so the root path
+    /// never changes.
+    ///
+    /// To repeat the resolution done by [`FileAssetReader::new`], which picks up a changed
+    /// `BEVY_ASSET_ROOT`:
+    ///
+    /// ```no_run
+    /// # use bevy_asset::io::file::FileAssetReader;
+    /// let reader = FileAssetReader::new("assets")
+    ///     .with_root_resolver(|_| Some(FileAssetReader::get_base_path().join("assets")));
+    /// ```
+    pub fn with_root_resolver(
+        mut self,
+        resolver: impl Fn(&Path) -> Option<PathBuf> + Send + Sync + 'static,
+    ) -> Self {
+        self.root_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Returns the directory assets are currently loaded from. This is
+    /// [`FileAssetReader::root_path`], unless it was re-resolved by the resolver set with
+    /// [`FileAssetReader::with_root_resolver`].
+    pub fn current_root(&self) -> PathBuf {
+        match &*self.resolved_root.read() {
+            Some(root) => root.clone(),
+            None => self.root_path.clone(),
+        }
+    }
+
+    /// Runs the root resolver if the current root path is no longer a directory, returning true if
+    /// it moved the root path to a directory that exists.
+    fn reresolve_root(&self) -> bool {
+        let Some(resolver) = &self.root_resolver else {
+            return false;
+        };
+        let current = self.current_root();
+        if current.is_dir() {
+            return false;
+        }
+        match resolver(&current) {
+            Some(root) if root != current && root.is_dir() => {
+                warn!(
+                    "Asset root {} is no longer a directory, loading assets from {} instead.",
+                    current.display(),
+                    root.display()
+                );
+                *self.resolved_root.write() = Some(root);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stops directory listings after `max_entries` entries.
+    ///
+    /// This protects tooling from hanging or running out of memory on pathological directories.
+    /// [`AssetReader::read_directory`] logs a warning when it truncates a listing, use
+    /// [`FileAssetReader::list_directory`] to find out whether a listing is complete.
+    pub fn with_max_directory_entries(mut self, max_entries: usize) -> Self {
+        self.max_directory_entries = Some(max_entries);
+        self
     }
 
     /// Returns the base path of the assets directory, which is normally the executable's parent
@@ -57,24 +418,523 @@ impl FileAssetReader {
         get_base_path()
     }
 
+    /// Returns the base path of the assets directory like [`FileAssetReader::get_base_path`],
+    /// along with the strategy that resolved it. This helps diagnose assets being looked up in
+    /// an unexpected directory.
+    pub fn get_base_path_with_source() -> (PathBuf, BasePathSource) {
+        get_base_path_with_source()
+    }
+
     /// Returns the root directory where assets are loaded from.
     ///
     /// See `get_base_path`.
     pub fn root_path(&self) -> &PathBuf {
         &self.root_path
     }
+
+    /// Runs `scope` with a reader that has this reader's configuration but reads from `root`
+    /// instead of [`FileAssetReader::root_path`].
+    ///
+    /// This reader is left untouched, so operations running concurrently on it are not
+    /// redirected, and nothing needs restoring if `scope` panics. This is handy for test fixtures
+    /// or for briefly reading from a backup root.
+    pub fn with_root_override<R>(
+        &self,
+        root: impl Into<PathBuf>,
+        scope: impl FnOnce(&FileAssetReader) -> R,
+    ) -> R {
+        let reader = FileAssetReader {
+            root_path: root.into(),
+            resolved_root: Arc::new(RwLock::new(None)),
+            ..self.clone()
+        };
+        scope(&reader)
+    }
+
+    /// Controls whether opening a symbolic link whose target does not exist fails with
+    /// [`AssetReaderError::DanglingSymlink`] or with [`AssetReaderError::NotFound`] (the default),
+    /// making a broken link indistinguishable from a missing one.
+    ///
+    /// Telling the two apart costs an extra filesystem call for every asset that is not found.
+    pub fn with_dangling_symlink_errors(mut self, enabled: bool) -> Self {
+        self.dangling_symlink_errors = enabled;
+        self
+    }
+
+    /// Transparently decompresses gzip, zstd and xz assets in
+    /// [`AssetReader::read`], detecting them by their leading magic bytes rather than by their
+    /// extension. Assets that do not start with a known magic are read unchanged.
+    ///
+    /// Seeking a decompressed asset is done by reading and discarding the skipped bytes.
+    #[cfg(feature = "compression")]
+    pub fn with_auto_decompress(mut self, enabled: bool) -> Self {
+        self.auto_decompress = enabled;
+        self
+    }
+
+    /// Logs a warning naming the asset whenever a reader returned by [`AssetReader::read`] is still
+    /// open after `threshold`, to help identify assets on slow or unresponsive mounts.
+    ///
+    /// The warning is issued by a lightweight task on the [`IoTaskPool`](bevy_tasks::IoTaskPool)
+    /// that is cancelled when the reader is dropped, so it fires even for reads that never finish.
+    #[cfg(feature = "multi_threaded")]
+    pub fn with_slow_read_warn_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_read_warn_threshold = Some(threshold);
+        self
+    }
+
+    /// Throttles the bytes read from asset files to about `bytes_per_sec`, shared between every
+    /// reader this reader (or any clone of it) opens, so that background loading does not starve
+    /// other processes of disk bandwidth. Reads may burst up to one second's worth of bytes after
+    /// being idle. Reads are not throttled by default.
+    ///
+    /// Meta files are not throttled. With a [`BlockingExecutor`], files are read from disk a 64 KiB
+    /// chunk at a time as their bytes are handed out, so the disk is read at most one chunk ahead
+    /// of the limit.
+    #[cfg(feature = "multi_threaded")]
+    pub fn with_read_rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.read_rate_limit = Some(Arc::new(throttle::ReadRateLimit::new(bytes_per_sec)));
+        self
+    }
+
+    /// Sets which other accesses to asset and meta files are allowed while they are open for
+    /// reading. Defaults to [`ReadShareMode::ReadWriteDelete`], the most permissive mode.
+    #[cfg(windows)]
+    pub fn with_read_share_mode(mut self, mode: ReadShareMode) -> Self {
+        self.read_share_mode = mode;
+        self
+    }
+
+    /// Reads asset and meta files with blocking filesystem calls run on `executor`, instead of
+    /// through `async_fs` and its own thread pool. This lets applications built on another async
+    /// runtime account for and manage file I/O like the rest of their blocking work.
+    ///
+    /// Each file is read a chunk at a time, with a blocking call per chunk, so large assets are
+    /// not loaded into memory at once. Files are opened with the standard library, so the share
+    /// mode set by `with_read_share_mode` does not apply.
+    #[cfg(feature = "multi_threaded")]
+    pub fn with_blocking_executor(mut self, executor: impl BlockingExecutor) -> Self {
+        self.blocking_executor = Some(Arc::new(executor));
+        self
+    }
+
+    /// Marks this reader, and every clone of it, as shutting down. Reads that have not opened
+    /// their file yet, including those waiting to retry, fail with
+    /// [`AssetReaderError::Shutdown`] instead of an I/O error.
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` once [`FileAssetReader::shutdown`] has been called.
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown.load(Ordering::Acquire)
+    }
+
+    /// Maps a failure to open the file at `full_path` to an [`AssetReaderError`]. The path is only
+    /// read as a link when it may be a dangling one, off the executor.
+    async fn open_error(&self, full_path: PathBuf, error: std::io::Error) -> AssetReaderError {
+        let link_target = if self.may_be_dangling_symlink(&error) {
+            let link = full_path.clone();
+            unblock(move || std::fs::read_link(link).ok()).await
+        } else {
+            None
+        };
+        self.map_open_error(full_path, error, link_target)
+    }
+
+    /// [`FileAssetReader::open_error`] for callers that already block.
+    fn open_error_blocking(&self, full_path: PathBuf, error: std::io::Error) -> AssetReaderError {
+        let link_target = self
+            .may_be_dangling_symlink(&error)
+            .then(|| std::fs::read_link(&full_path).ok())
+            .flatten();
+        self.map_open_error(full_path, error, link_target)
+    }
+
+    fn may_be_dangling_symlink(&self, error: &std::io::Error) -> bool {
+        self.dangling_symlink_errors && error.kind() == ErrorKind::NotFound
+    }
+
+    /// Maps a failure to open the file at `full_path`, given the target of the link at that path
+    /// if it is one.
+    fn map_open_error(
+        &self,
+        full_path: PathBuf,
+        error: std::io::Error,
+        link_target: Option<PathBuf>,
+    ) -> AssetReaderError {
+        if self.is_shut_down() {
+            return AssetReaderError::Shutdown;
+        }
+        #[cfg(feature = "multi_threaded")]
+        if executor::is_cancelled(&error) {
+            return AssetReaderError::Shutdown;
+        }
+        if error.kind() != ErrorKind::NotFound {
+            return error.into();
+        }
+        // reading the link only succeeds if the path itself exists, so it is the target that is missing
+        if let Some(target) = link_target {
+            return AssetReaderError::DanglingSymlink {
+                link: full_path,
+                target,
+            };
+        }
+        AssetReaderError::NotFound(full_path)
+    }
+
+    /// Collects the entries of the directory at `path`, honoring the limit set by
+    /// [`FileAssetReader::with_max_directory_entries`]. Errors reading individual entries are
+    /// handled according to `policy`.
+    ///
+    /// Unlike [`AssetReader::read_directory`], the returned [`DirectoryListing`] records whether
+    /// the listing was cut short, so callers can tell an incomplete listing from a complete one.
+    pub async fn list_directory(
+        &self,
+        path: &Path,
+        policy: ListPolicy,
+    ) -> Result<DirectoryListing, AssetReaderError> {
+        let mut entries = self.open_directory_entries(path).await?;
+        let mut listing = DirectoryListing::default();
+        while let Some(entry) = entries.next().await {
+            let entry = match (entry, policy) {
+                (Ok(entry), _) => entry,
+                (Err(_), ListPolicy::SkipErrors) => continue,
+                (Err(error), ListPolicy::FailFast) => return Err(error),
+                (Err(error), ListPolicy::Collect) => {
+                    listing.errors.push(error);
+                    continue;
+                }
+            };
+            if self
+                .max_directory_entries
+                .is_some_and(|max_entries| listing.paths.len() >= max_entries)
+            {
+                listing.truncated = true;
+                break;
+            }
+            listing.paths.push(entry);
+        }
+        Ok(listing)
+    }
+
+    /// Counts the entries of the directory at `path` that [`AssetReader::read_directory`] would
+    /// list, skipping meta files in the same way, as they are read rather than collecting them
+    /// into a list. Entries that cannot be read are not counted. The limit set by
+    /// [`FileAssetReader::with_max_directory_entries`] does not apply.
+    ///
+    /// The directory is read with blocking filesystem calls.
+    pub fn count_directory(&self, path: &Path) -> Result<usize, AssetReaderError> {
+        let full_path = self.full_path(path);
+        let entries = match std::fs::read_dir(&full_path) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(AssetReaderError::NotFound(full_path));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(entries
+            .filter_map(Result::ok)
+            .filter(|entry| !walk::is_meta_file(&entry.path()))
+            .count())
+    }
+
+    /// Lists the entries of the directory at `path` like [`AssetReader::read_directory`], in
+    /// batches of up to `batch_size` entries, so that a consumer working on one batch at a time
+    /// keeps a bounded working set. A `batch_size` of zero is treated as one.
+    ///
+    /// Entries are read from the directory as each batch is requested, not ahead of time.
+    pub async fn read_directory_batched(
+        &self,
+        path: &Path,
+        batch_size: usize,
+    ) -> Result<impl Stream<Item = Vec<PathBuf>> + Unpin + Send, AssetReaderError> {
+        let entries = self.read_directory(path).await?;
+        let batch_size = batch_size.max(1);
+        let batches = futures_lite::stream::unfold(entries, move |mut entries| async move {
+            let mut batch = Vec::new();
+            while batch.len() < batch_size {
+                match entries.next().await {
+                    Some(entry) => batch.push(entry),
+                    None => break,
+                }
+            }
+            (!batch.is_empty()).then_some((batch, entries))
+        });
+        Ok(Box::pin(batches))
+    }
+
+    /// Recursively lists every file in the directory at `path` along with its size in bytes,
+    /// sorted by path. Meta files are skipped unless `include_meta` is true.
+    ///
+    /// Summing the sizes gives the total footprint of the directory. The walk is performed with
+    /// blocking filesystem calls.
+    pub fn disk_usage(
+        &self,
+        path: &Path,
+        include_meta: bool,
+    ) -> Result<Vec<(PathBuf, u64)>, AssetReaderError> {
+        let root_path = self.current_root();
+        let files = walk::walk_files(&root_path, path, include_meta)?;
+        Ok(files
+            .into_iter()
+            .map(|file| (file.path, file.metadata.len()))
+            .collect())
+    }
+
+    /// Opens the asset at `path` like [`AssetReader::read`], but returns a reader that does not
+    /// borrow this reader, so it can be moved into a detached task that requires `'static`.
+    pub async fn read_owned(
+        self: Arc<Self>,
+        path: &Path,
+    ) -> Result<Box<dyn Reader>, AssetReaderError> {
+        self.read_asset(path).await
+    }
+
+    /// Reads the whole asset at `path`, which is expected to hold `expected_len` bytes, such as
+    /// the size reported by a metadata lookup made before the read.
+    ///
+    /// If fewer bytes are read, for example because another process truncated the file during a
+    /// non-atomic save, this fails with [`AssetReaderError::TruncatedDuringRead`] instead of
+    /// returning the partial contents, so the caller can retry the read.
+    pub async fn read_with_expected_len(
+        &self,
+        path: &Path,
+        expected_len: u64,
+    ) -> Result<Vec<u8>, AssetReaderError> {
+        let mut reader = self.read_asset(path).await?;
+        let mut bytes = Vec::new();
+        Reader::read_to_end(&mut reader, &mut bytes).await?;
+        let read = bytes.len() as u64;
+        if read < expected_len {
+            return Err(AssetReaderError::TruncatedDuringRead {
+                path: path.to_owned(),
+                expected: expected_len,
+                read,
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Recursively lists every asset in the directory at `path` that has no meta file, sorted by
+    /// path, for example to generate default meta files for them.
+    ///
+    /// Meta files are found in the same walk as the assets, so no file is checked individually.
+    /// The walk is performed with blocking filesystem calls.
+    pub fn assets_without_meta(&self, path: &Path) -> Result<Vec<PathBuf>, AssetReaderError> {
+        let root_path = self.current_root();
+        let files = walk::walk_files(&root_path, path, true)?;
+        let has_file = |path: &Path| {
+            files
+                .binary_search_by(|file| file.path.as_path().cmp(path))
+                .is_ok()
+        };
+        Ok(files
+            .iter()
+            .filter(|file| !walk::is_meta_file(&file.path) && !has_file(&get_meta_path(&file.path)))
+            .map(|file| file.path.clone())
+            .collect())
+    }
+
+    /// Recursively lists every asset in the directory at `path` whose content starts with `magic`,
+    /// sorted by path, regardless of its extension. This finds assets with a misleading extension,
+    /// such as a PNG image saved as `.jpg`. Meta files are skipped.
+    ///
+    /// Only the first `magic.len()` bytes of each asset are read, and each file is closed before
+    /// the next one is opened. Assets that cannot be read are skipped. The walk is performed with
+    /// blocking filesystem calls.
+    pub fn find_by_magic(
+        &self,
+        path: &Path,
+        magic: &[u8],
+    ) -> Result<Vec<PathBuf>, AssetReaderError> {
+        let root_path = self.current_root();
+        let files = walk::walk_files(&root_path, path, false)?;
+        Ok(files
+            .into_iter()
+            .filter(|file| starts_with_magic(&root_path.join(&file.path), magic))
+            .map(|file| file.path)
+            .collect())
+    }
+
+    /// Attempts to read the first byte of every asset in the directory at `path`, recursively,
+    /// returning the assets that could not be read along with their errors. Meta files are
+    /// skipped.
+    ///
+    /// This catches permission problems and unreadable files up front, for example in a startup
+    /// self-test. If the directory itself cannot be walked, its path is returned with the error.
+    pub async fn health_check(&self, path: &Path) -> Vec<(PathBuf, AssetReaderError)> {
+        let root_path = self.current_root();
+        let dir = path.to_owned();
+        let files = match unblock(move || walk::walk_files(&root_path, &dir, false)).await {
+            Ok(files) => files,
+            Err(error) => return vec![(path.to_owned(), error)],
+        };
+        let mut failures = Vec::new();
+        // each probe goes through `read`, which does its blocking work off the executor
+        for file in files {
+            let result = match self.read(&file.path).await {
+                Ok(mut reader) => reader.read(&mut [0; 1]).await.map_err(Into::into),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                failures.push((file.path, error));
+            }
+        }
+        failures
+    }
+
+    /// Reads the meta file of the asset at `path` and returns its top-level fields as a flat map.
+    ///
+    /// Keys are the field names. String values are returned as-is, every other value is returned
+    /// as RON. The file is parsed without knowing its types, which drops the names of structs
+    /// and enum variants, so these values do not round-trip: the `asset` field of a default meta
+    /// file maps to `{"loader":"...","settings":{...}}`, without its `Load` variant. Deserialize
+    /// a full [`AssetMeta`](crate::meta::AssetMeta) to get typed values. This is a lightweight
+    /// way to inspect a couple of plain fields, such as `meta_format_version`.
+    pub async fn read_meta_map(
+        &self,
+        path: &Path,
+    ) -> Result<HashMap<String, String>, AssetReaderError> {
+        let bytes = self.read_meta_bytes(path).await?;
+        let value: ron::Value = ron::de::from_bytes(&bytes).map_err(invalid_meta)?;
+        let ron::Value::Map(map) = value else {
+            return Err(invalid_meta("meta file does not contain a RON struct"));
+        };
+        map.iter()
+            .map(|(key, value)| Ok((ron_value_to_string(key)?, ron_value_to_string(value)?)))
+            .collect()
+    }
+}
+
+/// How [`FileAssetReader::list_directory`] handles errors reading individual directory entries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ListPolicy {
+    /// Silently skip entries that could not be read, like [`AssetReader::read_directory`] does.
+    #[default]
+    SkipErrors,
+    /// Fail the whole listing on the first entry that could not be read.
+    FailFast,
+    /// Keep listing, collecting the errors in [`DirectoryListing::errors`].
+    Collect,
+}
+
+/// The entries of a directory, as returned by [`FileAssetReader::list_directory`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DirectoryListing {
+    /// The listed entries, relative to the root path of the reader.
+    pub paths: Vec<PathBuf>,
+    /// True if the directory holds more entries than the configured maximum, meaning
+    /// [`DirectoryListing::paths`] is incomplete.
+    pub truncated: bool,
+    /// Errors reading individual entries, when listed with [`ListPolicy::Collect`].
+    pub errors: Vec<AssetReaderError>,
+}
+
+/// A stream of directory entries, or errors reading them.
+type EntryStream = dyn Stream<Item = Result<PathBuf, AssetReaderError>> + Unpin + Send;
+
+/// Ends `stream` after `max_entries` entries, warning if any entries of `dir` were dropped.
+fn cap_path_stream(
+    stream: Box<PathStream>,
+    dir: &Path,
+    max_entries: Option<usize>,
+) -> Box<PathStream> {
+    let Some(max_entries) = max_entries else {
+        return stream;
+    };
+    let dir = dir.to_owned();
+    let capped = stream
+        .enumerate()
+        .take_while(move |(index, _)| {
+            if *index < max_entries {
+                true
+            } else {
+                warn!(
+                    "Directory {} holds more than {max_entries} entries, the listing was truncated.",
+                    dir.display()
+                );
+                false
+            }
+        })
+        .map(|(_, path)| path);
+    Box::new(capped)
+}
+
+fn read_stdin() -> Result<VecReader, AssetReaderError> {
+    let mut bytes = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut bytes)?;
+    Ok(VecReader::new(bytes))
+}
+
+/// Returns true if `error` means the file is temporarily locked or busy because another process
+/// is using it.
+fn is_busy_error(error: &std::io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    matches!(
+        error.kind(),
+        ErrorKind::ExecutableFileBusy | ErrorKind::ResourceBusy
+    ) || (cfg!(windows)
+        && matches!(
+            error.raw_os_error(),
+            Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+        ))
+}
+
+/// Joins `path` onto `root`, ignoring a leading separator. See [`FileAssetReader::full_path`].
+fn join_root(root: &Path, path: &Path) -> PathBuf {
+    root.join(path.strip_prefix("/").unwrap_or(path))
+}
+
+/// Returns true if the file at `full_path` can be read and starts with `magic`.
+fn starts_with_magic(full_path: &Path, magic: &[u8]) -> bool {
+    let mut leading = vec![0; magic.len()];
+    std::fs::File::open(full_path)
+        .and_then(|mut file| file.read_exact(&mut leading))
+        .is_ok_and(|()| leading == magic)
+}
+
+fn ron_value_to_string(value: &ron::Value) -> Result<String, AssetReaderError> {
+    match value {
+        ron::Value::String(string) => Ok(string.clone()),
+        value => ron::to_string(value).map_err(invalid_meta),
+    }
+}
+
+fn invalid_meta(error: impl ToString) -> AssetReaderError {
+    std::io::Error::new(ErrorKind::InvalidData, error.to_string()).into()
 }
 
 /// A writer for the local filesystem.
 pub struct FileAssetWriter {
     root_path: PathBuf,
+    write_transform: Option<Arc<WriteTransform>>,
+    write_buffer_size: Option<usize>,
 }
 
 impl FileAssetWriter {
     /// Creates a new [`FileAssetWriter`] at a path relative to the executable's directory, optionally
     /// watching for changes.
+    ///
+    /// If `path` is absolute it is used as-is and the base path is ignored.
     pub fn new<P: AsRef<Path> + core::fmt::Debug>(path: P, create_root: bool) -> Self {
-        let root_path = get_base_path().join(path.as_ref());
+        let (root_path, _) = resolve_root_path(path.as_ref(), None);
+        Self::from_root(root_path, create_root)
+    }
+
+    /// Creates a new [`FileAssetWriter`] like [`FileAssetWriter::new`], but resolving the base
+    /// path like [`FileAssetReader::new_with_base_path_order`].
+    pub fn new_with_base_path_order<P: AsRef<Path> + core::fmt::Debug>(
+        path: P,
+        create_root: bool,
+        order: &[BasePathSource],
+    ) -> Self {
+        let (root_path, _) = resolve_root_path(path.as_ref(), Some(order));
+        Self::from_root(root_path, create_root)
+    }
+
+    fn from_root(root_path: PathBuf, create_root: bool) -> Self {
         if create_root {
             if let Err(e) = std::fs::create_dir_all(&root_path) {
                 error!(
@@ -84,6 +944,907 @@ impl FileAssetWriter {
                 );
             }
         }
-        Self { root_path }
+        Self {
+            root_path,
+            write_transform: None,
+            write_buffer_size: None,
+        }
+    }
+
+    /// Passes the bytes of every asset and meta file written by this writer through `transform`
+    /// before they reach the disk, for example to normalize line endings.
+    ///
+    /// Writes are buffered in memory, and the transform is applied to the whole file when the
+    /// writer is closed. Flushing does not write the buffered bytes. A writer dropped without
+    /// being closed applies the transform and writes the result as it is dropped, blocking until
+    /// it is written.
+    pub fn with_write_transform(
+        mut self,
+        transform: impl Fn(&Path, Vec<u8>) -> Vec<u8> + Send + Sync + 'static,
+    ) -> Self {
+        self.write_transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// Buffers the writers returned by this writer with a buffer of `size` bytes, coalescing
+    /// small writes into fewer, larger system calls. Buffered bytes are written out when the
+    /// writer is flushed.
+    ///
+    /// By default writes are unbuffered, which suits writers that already produce large chunks.
+    pub fn with_write_buffer(mut self, size: usize) -> Self {
+        self.write_buffer_size = Some(size);
+        self
+    }
+
+    /// Joins `path` onto the root path, ignoring a leading separator like
+    /// [`FileAssetReader`] does, so that a path such as `/foo` never escapes the root path.
+    fn full_path(&self, path: &Path) -> PathBuf {
+        join_root(&self.root_path, path)
+    }
+
+    /// Applies the configured buffering and [`WriteTransform`] (if any) to `writer`, which writes
+    /// to `path`.
+    fn wrap_writer(&self, path: &Path, mut writer: Box<Writer>) -> Box<Writer> {
+        if let Some(size) = self.write_buffer_size {
+            writer = Box::new(BufWriter::with_capacity(size, writer));
+        }
+        match &self.write_transform {
+            Some(transform) => Box::new(transform::TransformWriter::new(
+                path.to_owned(),
+                transform.clone(),
+                writer,
+            )),
+            None => writer,
+        }
+    }
+
+    /// Writes `bytes` to the asset at `path`, then reads the file back and checks that it holds
+    /// exactly `bytes` (after any [`WriteTransform`]), returning
+    /// [`AssetWriterError::VerificationFailed`] if it does not.
+    ///
+    /// This catches silent corruption on unreliable storage, at the cost of reading back every
+    /// byte written. The read back may be served from the operating system's page cache, so it
+    /// does not guarantee the bytes have reached the physical medium.
+    pub async fn write_verified(&self, path: &Path, bytes: &[u8]) -> Result<(), AssetWriterError> {
+        self.write_bytes(path, bytes).await?;
+        let full_path = self.full_path(path);
+        let written = unblock({
+            let full_path = full_path.clone();
+            move || std::fs::read(full_path)
+        })
+        .await?;
+        let matches = match &self.write_transform {
+            Some(transform) => written == transform(path, bytes.to_vec()),
+            None => written == bytes,
+        };
+        if !matches {
+            return Err(AssetWriterError::VerificationFailed(full_path));
+        }
+        Ok(())
+    }
+
+    /// Writes a group of related assets so that either all of them are published or none are.
+    ///
+    /// Each entry is first written to a temporary file next to its destination. Only once every
+    /// write has succeeded are the existing destinations renamed aside to backups and the
+    /// temporary files renamed into place. If any step fails, the new files are removed and the
+    /// backups restored on a best-effort basis before the error is returned, so the previous
+    /// versions of the assets are kept. The backups are removed once the whole group is published.
+    ///
+    /// Renames are atomic per file, so readers may briefly observe some but not all of the group
+    /// while it is being published.
+    pub async fn write_group(
+        &self,
+        entries: &[(PathBuf, Vec<u8>)],
+    ) -> Result<(), AssetWriterError> {
+        let mut temp_paths = Vec::with_capacity(entries.len());
+        for (path, bytes) in entries {
+            let temp_path = temp_path_for(path);
+            let result = self.write_bytes(&temp_path, bytes).await;
+            temp_paths.push(temp_path);
+            if let Err(error) = result {
+                self.remove_best_effort(&temp_paths).await;
+                return Err(error);
+            }
+        }
+        let renames: Vec<_> = temp_paths
+            .iter()
+            .zip(entries)
+            .map(|(temp_path, (path, _))| (self.full_path(temp_path), self.full_path(path)))
+            .collect();
+        unblock(move || publish_group(&renames)).await?;
+        Ok(())
+    }
+
+    /// Atomically replaces the asset at `target` with the file at `source`, creating the parent
+    /// directories of `target` if needed. Both paths are relative to the root path.
+    ///
+    /// When both paths are on the same filesystem, `source` is renamed over `target`. Otherwise
+    /// `source` is copied to a temporary file next to `target`, which is then renamed over
+    /// `target` before `source` is removed. Either way, readers of `target` observe either the old
+    /// or the new contents, never a partial file.
+    pub async fn replace_from(&self, target: &Path, source: &Path) -> Result<(), AssetWriterError> {
+        let full_target = self.full_path(target);
+        let full_source = self.full_path(source);
+        unblock(move || {
+            if let Some(parent) = full_target.parent() {
+                create_dir_all_concurrent(parent)?;
+            }
+            replace_file(&full_source, &full_target)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Appends `bytes` to the file at `path`, creating it (and its parent directories) if needed.
+    /// If the file would grow beyond `max_size` bytes, it is rotated first: `path.1` becomes
+    /// `path.2` and so on, `path` becomes `path.1`, and `bytes` start a fresh file. Only `keep`
+    /// rotated files are kept, older ones are removed. With a `keep` of zero, the full file is
+    /// simply discarded.
+    ///
+    /// An empty file is never rotated, so a single append larger than `max_size` is still written
+    /// in full. Appended bytes are not passed through the [`WriteTransform`], if any.
+    pub async fn append_rotating(
+        &self,
+        path: &Path,
+        bytes: &[u8],
+        max_size: u64,
+        keep: usize,
+    ) -> Result<(), AssetWriterError> {
+        let full_path = self.full_path(path);
+        let bytes = bytes.to_vec();
+        unblock(move || append_rotating_file(&full_path, &bytes, max_size, keep)).await?;
+        Ok(())
+    }
+
+    async fn remove_best_effort(&self, paths: &[PathBuf]) {
+        for path in paths {
+            // this only runs while recovering from another error, which is the one worth reporting
+            let _ = self.remove(path).await;
+        }
+    }
+}
+
+/// The position of a reader that can only seek forward by reading and discarding, for readers
+/// whose contents cannot be seeked as they are stored.
+#[derive(Default)]
+struct DiscardingSeek {
+    position: u64,
+    /// The position being seeked to, kept while a seek is pending.
+    target: Option<u64>,
+}
+
+impl DiscardingSeek {
+    /// Reads from `reader` into `buf`, advancing the position by the bytes read.
+    fn poll_read<R: AsyncRead + ?Sized>(
+        &mut self,
+        reader: Pin<&mut R>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let n = ready!(reader.poll_read(cx, buf))?;
+        self.position += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    /// Reads and discards from `reader` until the position is `offset` bytes further along, or
+    /// the reader ends, and returns the new position.
+    fn poll_seek_forward<R: AsyncRead + ?Sized>(
+        &mut self,
+        mut reader: Pin<&mut R>,
+        cx: &mut Context<'_>,
+        offset: u64,
+    ) -> Poll<std::io::Result<u64>> {
+        let target = *self.target.get_or_insert(self.position + offset);
+        let mut scratch = [0; 512];
+        while self.position < target {
+            let remaining = usize::try_from(target - self.position).unwrap_or(usize::MAX);
+            let len = scratch.len().min(remaining);
+            match ready!(self.poll_read(reader.as_mut(), cx, &mut scratch[..len])) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(error) => {
+                    self.target = None;
+                    return Poll::Ready(Err(error));
+                }
+            }
+        }
+        self.target = None;
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+/// Runs the blocking filesystem operation `f` on the blocking thread pool, so that it does not
+/// stall the async executor.
+#[cfg(feature = "multi_threaded")]
+async fn unblock<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    blocking::unblock(f).await
+}
+
+/// Runs the blocking filesystem operation `f` in place, like every other file operation of the
+/// sync backend.
+#[cfg(not(feature = "multi_threaded"))]
+async fn unblock<T>(f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+/// The blocking part of [`FileAssetWriter::append_rotating`].
+fn append_rotating_file(
+    full_path: &Path,
+    bytes: &[u8],
+    max_size: u64,
+    keep: usize,
+) -> std::io::Result<()> {
+    if let Some(parent) = full_path.parent() {
+        create_dir_all_concurrent(parent)?;
+    }
+    let size = match std::fs::metadata(full_path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == ErrorKind::NotFound => 0,
+        Err(e) => return Err(e),
+    };
+    if size > 0 && size.saturating_add(bytes.len() as u64) > max_size {
+        remove_if_exists(&rotated_path(full_path, keep))?;
+        for n in (1..keep).rev() {
+            rename_if_exists(&rotated_path(full_path, n), &rotated_path(full_path, n + 1))?;
+        }
+        rename_if_exists(full_path, &rotated_path(full_path, 1))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(full_path)?;
+    file.write_all(bytes)
+}
+
+/// Returns the path of the `n`th rotated file of `path`, or `path` itself if `n` is zero.
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return path.to_owned();
+    }
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{n}"));
+    rotated.into()
+}
+
+fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Returns a unique temporary path in the same directory as `path`, so that renaming it onto
+/// `path` stays on the same filesystem.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{file_name}.{}.tmp", uuid::Uuid::new_v4()))
+}
+
+/// The blocking part of publishing a [`FileAssetWriter::write_group`]: renames each temporary
+/// file over its destination, given as `(temporary, destination)` pairs of full paths. Existing
+/// destination files are renamed aside first, and restored if any rename fails.
+fn publish_group(renames: &[(PathBuf, PathBuf)]) -> std::io::Result<()> {
+    let mut backups = Vec::with_capacity(renames.len());
+    let mut published = 0;
+    let mut result = Ok(());
+    for (_, destination) in renames {
+        // only files are replaced, renaming over anything else fails below
+        if !std::fs::symlink_metadata(destination).is_ok_and(|metadata| metadata.is_file()) {
+            backups.push(None);
+            continue;
+        }
+        let backup = temp_path_for(destination);
+        if let Err(e) = std::fs::rename(destination, &backup) {
+            result = Err(e);
+            break;
+        }
+        backups.push(Some(backup));
+    }
+    if result.is_ok() {
+        for (temp_path, destination) in renames {
+            if let Err(e) = std::fs::rename(temp_path, destination) {
+                result = Err(e);
+                break;
+            }
+            published += 1;
+        }
+    }
+    // the failed rename is the error worth reporting, so recovering from it is best-effort
+    if result.is_err() {
+        for (_, destination) in &renames[..published] {
+            let _ = std::fs::remove_file(destination);
+        }
+        for ((_, destination), backup) in renames.iter().zip(&backups) {
+            if let Some(backup) = backup {
+                let _ = std::fs::rename(backup, destination);
+            }
+        }
+        for (temp_path, _) in &renames[published..] {
+            let _ = std::fs::remove_file(temp_path);
+        }
+    } else {
+        for backup in backups.iter().flatten() {
+            let _ = std::fs::remove_file(backup);
+        }
+    }
+    result
+}
+
+/// Atomically replaces the file at `full_target` with the one at `full_source`, copying it
+/// through a temporary file if they are on different filesystems. See
+/// [`FileAssetWriter::replace_from`].
+fn replace_file(full_source: &Path, full_target: &Path) -> std::io::Result<()> {
+    match std::fs::rename(full_source, full_target) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            let temp_path = temp_path_for(full_target);
+            let result = std::fs::copy(full_source, &temp_path)
+                .and_then(|_| std::fs::rename(&temp_path, full_target));
+            if let Err(e) = result {
+                // the failed copy or rename is the error worth reporting
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(e);
+            }
+            std::fs::remove_file(full_source)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Creates the directory at `path` and any missing parents. Losing the race to create one of them
+/// to a concurrent writer is not an error, as long as `path` ends up being a directory.
+fn create_dir_all_concurrent(path: &Path) -> std::io::Result<()> {
+    match std::fs::create_dir_all(path) {
+        Err(e) if e.kind() == ErrorKind::AlreadyExists && path.is_dir() => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_tasks::block_on;
+    use core::ops::Deref;
+    use std::{sync::Barrier, thread};
+
+    /// A unique path for a test to create a directory at, removed along with everything in it
+    /// when this is dropped, including when the test fails.
+    #[derive(Debug)]
+    pub(super) struct TempDir(PathBuf);
+
+    impl TempDir {
+        /// Returns a unique path under the system temp directory, named after `name`.
+        pub(super) fn new(name: &str) -> Self {
+            Self::in_dir(&env::temp_dir(), name)
+        }
+
+        /// Returns a unique path under `parent`, named after `name`.
+        pub(super) fn in_dir(parent: &Path, name: &str) -> Self {
+            Self(parent.join(format!("bevy_asset_{name}_{}", uuid::Uuid::new_v4())))
+        }
+    }
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempDir {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            // nothing may have been created at the path
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn concurrent_writes_into_new_directory() {
+        let root = TempDir::new("writes");
+        let writer = FileAssetWriter::new(&root, false);
+        let deep = Path::new("a/b/c/d/e");
+        let writers = 32;
+        let barrier = Barrier::new(writers);
+        thread::scope(|scope| {
+            for i in 0..writers {
+                let (writer, barrier) = (&writer, &barrier);
+                scope.spawn(move || {
+                    let path = deep.join(format!("{i}.bin"));
+                    barrier.wait();
+                    block_on(writer.write_bytes(&path, &[i as u8])).unwrap();
+                });
+            }
+        });
+        let written = std::fs::read_dir(root.join(deep)).unwrap().count();
+        assert_eq!(written, writers);
+    }
+
+    #[test]
+    fn leading_separator_stays_under_the_root() {
+        let root = TempDir::new("leading");
+        let writer = FileAssetWriter::new(&root, true);
+        let reader = FileAssetReader::new(&root);
+        block_on(writer.write_bytes(Path::new("/foo/a.txt"), b"a")).unwrap();
+        let written = std::fs::read(root.join("foo/a.txt"));
+        let mut bytes = Vec::new();
+        let read = block_on(async {
+            let mut asset = reader.read(Path::new("/foo/a.txt")).await?;
+            Reader::read_to_end(&mut asset, &mut bytes).await?;
+            Ok::<_, AssetReaderError>(())
+        });
+        assert_eq!(written.unwrap(), b"a");
+        assert_eq!(read, Ok(()));
+        assert_eq!(bytes, b"a");
+    }
+
+    #[test]
+    fn append_rotating_rotates_past_the_size_limit() {
+        let root = TempDir::new("rotating");
+        let writer = FileAssetWriter::new(&root, true);
+        let log = Path::new("logs/a.log");
+        let append = |bytes: &[u8]| block_on(writer.append_rotating(log, bytes, 4, 2)).unwrap();
+        let read = |name: &str| std::fs::read(root.join("logs").join(name)).ok();
+        append(b"12");
+        append(b"34");
+        let filled = (read("a.log"), read("a.log.1"));
+        append(b"5");
+        let rotated = (read("a.log"), read("a.log.1"));
+        append(b"6789");
+        append(b"abcdef");
+        append(b"g");
+        let kept = (
+            read("a.log"),
+            read("a.log.1"),
+            read("a.log.2"),
+            read("a.log.3"),
+        );
+        assert_eq!(filled, (Some(b"1234".to_vec()), None));
+        assert_eq!(rotated, (Some(b"5".to_vec()), Some(b"1234".to_vec())));
+        assert_eq!(
+            kept,
+            (
+                Some(b"g".to_vec()),
+                Some(b"abcdef".to_vec()),
+                Some(b"6789".to_vec()),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn write_group_publishes_nothing_if_a_write_fails() {
+        let root = TempDir::new("group");
+        std::fs::create_dir_all(&root).unwrap();
+        // a file where the second entry needs a directory, so writing it fails
+        std::fs::write(root.join("blocker"), b"").unwrap();
+        let writer = FileAssetWriter::new(&root, false);
+        let entries = [
+            (PathBuf::from("a.txt"), b"a".to_vec()),
+            (PathBuf::from("blocker/b.txt"), b"b".to_vec()),
+        ];
+        let result = block_on(writer.write_group(&entries));
+        let mut left: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        left.sort();
+        assert!(result.is_err());
+        assert_eq!(left, ["blocker"]);
+    }
+
+    #[test]
+    fn write_group_restores_previous_versions_if_a_rename_fails() {
+        let root = TempDir::new("restore");
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("a.txt"), b"old").unwrap();
+        // a directory in place of the second entry, so renaming it into place fails
+        std::fs::write(root.join("b/inner.txt"), b"inner").unwrap();
+        let writer = FileAssetWriter::new(&root, false);
+        let entries = [
+            (PathBuf::from("a.txt"), b"new".to_vec()),
+            (PathBuf::from("b"), b"b".to_vec()),
+        ];
+        let result = block_on(writer.write_group(&entries));
+        let a = std::fs::read(root.join("a.txt"));
+        let inner = std::fs::read(root.join("b/inner.txt"));
+        let mut left: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        left.sort();
+        assert!(result.is_err());
+        assert_eq!(a.unwrap(), b"old");
+        assert_eq!(inner.unwrap(), b"inner");
+        assert_eq!(left, ["a.txt", "b"]);
+    }
+
+    #[test]
+    fn write_transform_sees_the_whole_file() {
+        use futures_lite::AsyncWriteExt;
+
+        let root = TempDir::new("transform");
+        let writer = FileAssetWriter::new(&root, true)
+            .with_write_transform(|_, bytes| [&b"["[..], &bytes[..], &b"]"[..]].concat());
+        block_on(async {
+            for (path, close) in [("closed.txt", true), ("dropped.txt", false)] {
+                let mut file = writer.write(Path::new(path)).await.unwrap();
+                file.write_all(b"ab").await.unwrap();
+                file.flush().await.unwrap();
+                file.write_all(b"cd").await.unwrap();
+                file.flush().await.unwrap();
+                if close {
+                    file.close().await.unwrap();
+                }
+            }
+            writer
+                .write_bytes(Path::new("bytes.txt"), b"abcd")
+                .await
+                .unwrap();
+        });
+        let closed = std::fs::read(root.join("closed.txt")).unwrap();
+        let dropped = std::fs::read(root.join("dropped.txt")).unwrap();
+        let bytes = std::fs::read(root.join("bytes.txt")).unwrap();
+        assert_eq!(closed, b"[abcd]");
+        // dropping the writer without closing it leaves only the empty file it created
+        assert!(dropped.is_empty());
+        assert_eq!(bytes, b"[abcd]");
+    }
+
+    #[test]
+    fn read_directory_concat_seeks_across_files() {
+        use crate::io::AsyncSeekForwardExt;
+
+        let root = TempDir::new("concat");
+        std::fs::create_dir_all(root.join("parts/nested")).unwrap();
+        std::fs::write(root.join("parts/b.txt"), b"de").unwrap();
+        std::fs::write(root.join("parts/a.txt"), b"abc").unwrap();
+        std::fs::write(root.join("parts/a.txt.meta"), b"meta").unwrap();
+        std::fs::write(root.join("parts/c.txt"), b"fgh").unwrap();
+        std::fs::write(root.join("parts/nested/d.txt"), b"nested").unwrap();
+        let reader = FileAssetReader::new(&root);
+        let mut bytes = Vec::new();
+        let read = block_on(async {
+            let mut concat = reader.read_directory_concat(Path::new("parts")).await?;
+            // from the middle of the first file to the middle of the last one
+            let first = concat.seek_forward(1).await?;
+            let second = concat.seek_forward(5).await?;
+            Reader::read_to_end(&mut concat, &mut bytes).await?;
+            Ok::<_, AssetReaderError>((first, second))
+        });
+        assert_eq!(read, Ok((1, 6)));
+        assert_eq!(bytes, b"gh");
+    }
+
+    #[cfg(feature = "multi_threaded")]
+    #[test]
+    fn read_rate_limit_throttles_reads() {
+        use std::time::Instant;
+
+        let root = TempDir::new("throttle");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.bin"), [7; 15_000]).unwrap();
+        // the first second's worth of bytes is read right away, the other half second is waited
+        let reader = FileAssetReader::new(&root).with_read_rate_limit(10_000);
+        let mut bytes = Vec::new();
+        let started = Instant::now();
+        let read = block_on(async {
+            let mut asset = reader.read(Path::new("a.bin")).await?;
+            Reader::read_to_end(&mut asset, &mut bytes).await?;
+            Ok::<_, AssetReaderError>(())
+        });
+        let elapsed = started.elapsed();
+        assert_eq!(read, Ok(()));
+        assert_eq!(bytes.len(), 15_000);
+        assert!(elapsed >= Duration::from_millis(450), "{elapsed:?}");
+    }
+
+    #[test]
+    fn diff_trees_reports_added_removed_and_changed_files() {
+        let root = TempDir::new("diff");
+        for (path, contents) in [
+            ("base/same.txt", "same"),
+            ("base/edited.txt", "old"),
+            ("base/resized.txt", "short"),
+            ("base/removed.txt", "removed"),
+            ("base/sub/nested.txt", "nested"),
+            ("other/same.txt", "same"),
+            ("other/edited.txt", "new"),
+            ("other/resized.txt", "longer"),
+            ("other/sub/nested.txt", "nested"),
+            ("other/sub/added.txt", "added"),
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        let base = FileAssetReader::new(root.join("base"));
+        let other = FileAssetReader::new(root.join("other"));
+        let diff = base.diff_trees(&other, Path::new(""), TreeComparison::Contents);
+        assert_eq!(
+            diff,
+            Ok(TreeDiff {
+                added: vec![PathBuf::from("sub/added.txt")],
+                removed: vec![PathBuf::from("removed.txt")],
+                changed: vec![PathBuf::from("edited.txt"), PathBuf::from("resized.txt")],
+            })
+        );
+    }
+
+    #[test]
+    fn read_directory_batched_yields_bounded_batches() {
+        let root = TempDir::new("batched");
+        std::fs::create_dir_all(root.join("dir")).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"] {
+            std::fs::write(root.join("dir").join(name), name).unwrap();
+        }
+        let reader = FileAssetReader::new(&root);
+        let batches = block_on(async {
+            let batches = reader.read_directory_batched(Path::new("dir"), 2).await?;
+            Ok::<_, AssetReaderError>(batches.collect::<Vec<_>>().await)
+        })
+        .unwrap();
+        let sizes: Vec<_> = batches.iter().map(Vec::len).collect();
+        let mut paths = batches.concat();
+        paths.sort();
+        assert_eq!(sizes, [2, 2, 1]);
+        assert_eq!(
+            paths,
+            ["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"].map(|name| Path::new("dir").join(name))
+        );
+    }
+
+    #[test]
+    fn find_by_magic_matches_leading_bytes() {
+        const PNG: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+        let root = TempDir::new("magic");
+        std::fs::create_dir_all(root.join("images/nested")).unwrap();
+        for (path, contents) in [
+            ("images/real.png", [PNG, &b"data"[..]].concat()),
+            ("images/real.png.meta", PNG.to_vec()),
+            ("images/nested/misnamed.dat", PNG.to_vec()),
+            ("images/truncated.png", PNG[..4].to_vec()),
+            ("images/text.png", b"not an image".to_vec()),
+        ] {
+            std::fs::write(root.join(path), contents).unwrap();
+        }
+        let reader = FileAssetReader::new(&root);
+        let found = reader.find_by_magic(Path::new("images"), PNG);
+        assert_eq!(
+            found,
+            Ok(vec![
+                PathBuf::from("images/nested/misnamed.dat"),
+                PathBuf::from("images/real.png"),
+            ])
+        );
+    }
+
+    #[test]
+    fn file_io_config_applies_to_created_readers() {
+        let root = TempDir::new("config");
+        std::fs::create_dir_all(root.join("dir")).unwrap();
+        std::fs::write(root.join("dir/a.txt"), b"a").unwrap();
+        std::fs::write(root.join("dir/b.txt"), b"b").unwrap();
+        let config = FileIoConfig {
+            max_directory_entries: Some(1),
+            ..Default::default()
+        };
+        let limited = block_on(
+            config
+                .reader(&root)
+                .list_directory(Path::new("dir"), ListPolicy::FailFast),
+        )
+        .unwrap();
+        let unlimited = block_on(
+            FileIoConfig::default()
+                .reader(&root)
+                .list_directory(Path::new("dir"), ListPolicy::FailFast),
+        )
+        .unwrap();
+        assert_eq!((limited.paths.len(), limited.truncated), (1, true));
+        assert_eq!((unlimited.paths.len(), unlimited.truncated), (2, false));
+    }
+
+    #[test]
+    fn search_paths_fall_through_to_later_roots() {
+        let root = TempDir::new("search");
+        std::fs::create_dir_all(root.join("first")).unwrap();
+        std::fs::create_dir_all(root.join("second")).unwrap();
+        std::fs::write(root.join("second/a.txt"), b"a").unwrap();
+        std::fs::write(root.join("second/a.txt.meta"), b"meta").unwrap();
+        let reader = SearchPathAssetReader::new([root.join("first"), root.join("second")]);
+        let mut bytes = Vec::new();
+        let mut meta = Vec::new();
+        let read = block_on(async {
+            let mut asset = reader.read(Path::new("a.txt")).await?;
+            Reader::read_to_end(&mut asset, &mut bytes).await?;
+            let mut asset_meta = reader.read_meta(Path::new("a.txt")).await?;
+            Reader::read_to_end(&mut asset_meta, &mut meta).await?;
+            Ok::<_, AssetReaderError>(())
+        });
+        let missing = block_on(reader.read_meta(Path::new("b.txt"))).map(|_| ());
+        assert_eq!(read, Ok(()));
+        assert_eq!(bytes, b"a");
+        assert_eq!(meta, b"meta");
+        assert!(matches!(
+            missing,
+            Err(AssetReaderError::NotFoundInSearchPaths { searched, .. }) if searched.len() == 2
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_symlinks_are_reported_when_enabled() {
+        let root = TempDir::new("dangling");
+        std::fs::create_dir_all(&root).unwrap();
+        std::os::unix::fs::symlink(root.join("missing.txt"), root.join("link.txt")).unwrap();
+        let reader = FileAssetReader::new(&root);
+        let default = block_on(reader.read(Path::new("link.txt"))).map(|_| ());
+        let reader = reader.with_dangling_symlink_errors(true);
+        let enabled = block_on(reader.read(Path::new("link.txt"))).map(|_| ());
+        assert_eq!(
+            default,
+            Err(AssetReaderError::NotFound(root.join("link.txt")))
+        );
+        assert_eq!(
+            enabled,
+            Err(AssetReaderError::DanglingSymlink {
+                link: root.join("link.txt"),
+                target: root.join("missing.txt"),
+            })
+        );
+    }
+
+    #[test]
+    fn remove_if_matches_only_removes_the_expected_contents() {
+        let root = TempDir::new("remove_if_matches");
+        let writer = FileAssetWriter::new(&root, true);
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        std::fs::write(root.join("b.txt"), b"newer").unwrap();
+        let expected = FileAssetReader::new(&root)
+            .hash_asset(Path::new("a.txt"), Fnv1aHasher::default())
+            .unwrap();
+        let matching =
+            writer.remove_if_matches(Path::new("a.txt"), Fnv1aHasher::default(), &expected);
+        let mismatched =
+            writer.remove_if_matches(Path::new("b.txt"), Fnv1aHasher::default(), &expected);
+        assert!(matching.is_ok());
+        assert!(!root.join("a.txt").exists());
+        assert!(
+            matches!(mismatched, Err(AssetWriterError::Conflict(path)) if path == root.join("b.txt"))
+        );
+        assert_eq!(std::fs::read(root.join("b.txt")).unwrap(), b"newer");
+    }
+
+    #[test]
+    fn reads_of_a_file_that_shrank_report_the_truncation() {
+        let root = TempDir::new("truncated");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.txt"), b"0123456789").unwrap();
+        let reader = FileAssetReader::new(&root);
+        let expected = std::fs::metadata(root.join("a.txt")).unwrap().len();
+        // a non-atomic save truncating the file after its size was looked up
+        std::fs::write(root.join("a.txt"), b"0123").unwrap();
+        let truncated = block_on(reader.read_with_expected_len(Path::new("a.txt"), expected));
+        let whole = block_on(reader.read_with_expected_len(Path::new("a.txt"), 4));
+        assert_eq!(
+            truncated,
+            Err(AssetReaderError::TruncatedDuringRead {
+                path: PathBuf::from("a.txt"),
+                expected: 10,
+                read: 4,
+            })
+        );
+        assert_eq!(whole, Ok(b"0123".to_vec()));
+    }
+
+    #[cfg(feature = "test_util")]
+    #[test]
+    fn busy_files_are_retried_by_default() {
+        let root = TempDir::new("busy");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        let reader = FileAssetReader::new(&root);
+        let read = || {
+            block_on(async {
+                let mut bytes = Vec::new();
+                let mut asset = reader.read(Path::new("a.txt")).await?;
+                Reader::read_to_end(&mut asset, &mut bytes).await?;
+                Ok::<_, AssetReaderError>(bytes)
+            })
+        };
+        // one busy error per retry is still recovered from
+        let attempts = DEFAULT_BUSY_RETRY.attempts as usize;
+        reader.inject_fault(FaultSpec::kind(attempts, ErrorKind::ResourceBusy));
+        let retried = read();
+        reader.inject_fault(FaultSpec::kind(attempts + 1, ErrorKind::ResourceBusy));
+        let exhausted = read();
+        assert_eq!(retried, Ok(b"a".to_vec()));
+        assert!(
+            matches!(exhausted, Err(AssetReaderError::Io(error)) if error.kind() == ErrorKind::ResourceBusy)
+        );
+    }
+
+    #[test]
+    fn reads_after_shutdown_fail_cleanly() {
+        let root = TempDir::new("shutdown");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        let reader = FileAssetReader::new(&root);
+        let clone = reader.clone();
+        reader.shutdown();
+        let read = block_on(clone.read(Path::new("a.txt"))).map(|_| ());
+        let read_meta = block_on(clone.read_meta(Path::new("a.txt"))).map(|_| ());
+        assert_eq!(read, Err(AssetReaderError::Shutdown));
+        assert_eq!(read_meta, Err(AssetReaderError::Shutdown));
+    }
+
+    #[cfg(feature = "multi_threaded")]
+    #[test]
+    fn reads_dropped_by_executor_fail_cleanly() {
+        use core::sync::atomic::AtomicUsize;
+        use futures_lite::{future::poll_once, FutureExt};
+
+        #[derive(Default)]
+        struct TestExecutor {
+            spawned: AtomicUsize,
+        }
+
+        impl BlockingExecutor for TestExecutor {
+            fn spawn_blocking(
+                &self,
+                task: Box<dyn FnOnce() + Send>,
+            ) -> bevy_tasks::BoxedFuture<'static, ()> {
+                match self.spawned.fetch_add(1, Ordering::Relaxed) {
+                    // the first read stays in flight until it is cancelled
+                    0 => Box::pin(async move {
+                        let _task = task;
+                        core::future::pending::<()>().await;
+                    }),
+                    // the second read is dropped without running
+                    1 => Box::pin(async {}),
+                    _ => {
+                        task();
+                        Box::pin(async {})
+                    }
+                }
+            }
+        }
+
+        let root = TempDir::new("shutdown");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        let reader = FileAssetReader::new(&root).with_blocking_executor(TestExecutor::default());
+        let mut in_flight = reader.read(Path::new("a.txt")).boxed_local();
+        let pending = block_on(poll_once(&mut in_flight)).is_none();
+        drop(in_flight);
+        let dropped = block_on(reader.read(Path::new("a.txt"))).map(|_| ());
+        let mut bytes = Vec::new();
+        let next_read = block_on(async {
+            let mut asset = reader.read(Path::new("a.txt")).await?;
+            Reader::read_to_end(&mut asset, &mut bytes).await?;
+            Ok::<_, AssetReaderError>(())
+        });
+        assert!(pending);
+        // only the dropped read is abandoned, the reader itself is not shut down
+        assert_eq!(dropped, Err(AssetReaderError::Shutdown));
+        assert!(!reader.is_shut_down());
+        assert_eq!(next_read, Ok(()));
+        assert_eq!(bytes, b"a");
     }
 }
@@ -0,0 +1,161 @@
+use super::FileAssetReader;
+use crate::io::{AssetReader, AssetReaderError, PathStream, Reader, VecReader};
+use alloc::{borrow::ToOwned, boxed::Box, sync::Arc, vec::Vec};
+use bevy_platform_support::collections::HashMap;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+
+/// Whether a [`JournalEntry`] records an asset or its meta file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum JournalEntryKind {
+    /// The entry was recorded by [`AssetReader::read`].
+    Asset,
+    /// The entry was recorded by [`AssetReader::read_meta`].
+    Meta,
+}
+
+/// A single successful read recorded by a [`RecordingAssetReader`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Whether the asset or its meta file was read.
+    pub kind: JournalEntryKind,
+    /// The path that was read, relative to the root path of the reader.
+    pub path: PathBuf,
+    /// The bytes that were read.
+    pub bytes: Vec<u8>,
+}
+
+/// The reads recorded by a [`RecordingAssetReader`], in the order they completed.
+///
+/// Clones share the same entries, so a journal can be kept while its reader is handed to an
+/// [`AssetSource`](crate::io::AssetSource).
+#[derive(Clone, Default)]
+pub struct AssetJournal {
+    entries: Arc<Mutex<Vec<JournalEntry>>>,
+}
+
+impl AssetJournal {
+    /// Returns a copy of the entries recorded so far.
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries.lock().clone()
+    }
+
+    fn record(&self, kind: JournalEntryKind, path: &Path, bytes: Vec<u8>) {
+        self.entries.lock().push(JournalEntry {
+            kind,
+            path: path.to_owned(),
+            bytes,
+        });
+    }
+}
+
+/// A [`FileAssetReader`] that records the path and bytes of every successful
+/// [`AssetReader::read`] and [`AssetReader::read_meta`] in an [`AssetJournal`], to be replayed
+/// later with a [`ReplayAssetReader`].
+///
+/// Each asset is read in full when it is opened so its bytes can be recorded.
+pub struct RecordingAssetReader {
+    reader: FileAssetReader,
+    journal: AssetJournal,
+}
+
+impl RecordingAssetReader {
+    /// Wraps `reader`, recording its reads in a new [`AssetJournal`].
+    pub fn new(reader: FileAssetReader) -> Self {
+        Self {
+            reader,
+            journal: AssetJournal::default(),
+        }
+    }
+
+    /// Returns the journal this reader records to.
+    pub fn journal(&self) -> &AssetJournal {
+        &self.journal
+    }
+}
+
+impl AssetReader for RecordingAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let mut reader = self.reader.read(path).await?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        self.journal
+            .record(JournalEntryKind::Asset, path, bytes.clone());
+        Ok(VecReader::new(bytes))
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let mut reader = self.reader.read_meta(path).await?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        self.journal
+            .record(JournalEntryKind::Meta, path, bytes.clone());
+        Ok(VecReader::new(bytes))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        self.reader.read_directory(path).await
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        self.reader.is_directory(path).await
+    }
+}
+
+/// An [`AssetReader`] that serves reads from an [`AssetJournal`] recorded by a
+/// [`RecordingAssetReader`], without touching the disk.
+///
+/// Paths that were never recorded are reported as [`AssetReaderError::NotFound`]. If a path was
+/// recorded more than once, the last recording is served. Directories are not recorded, so
+/// directory listings always fail with [`AssetReaderError::NotFound`].
+pub struct ReplayAssetReader {
+    entries: HashMap<(JournalEntryKind, PathBuf), Arc<[u8]>>,
+}
+
+impl ReplayAssetReader {
+    /// Creates a reader serving the entries of `journal`.
+    pub fn new(journal: &AssetJournal) -> Self {
+        Self::from_entries(journal.entries())
+    }
+
+    /// Creates a reader serving `entries`, for example loaded from a journal saved to disk.
+    pub fn from_entries(entries: impl IntoIterator<Item = JournalEntry>) -> Self {
+        Self {
+            entries: entries
+                .into_iter()
+                .map(|entry| ((entry.kind, entry.path), entry.bytes.into()))
+                .collect(),
+        }
+    }
+
+    fn replay(&self, kind: JournalEntryKind, path: &Path) -> Result<VecReader, AssetReaderError> {
+        self.entries
+            .get(&(kind, path.to_owned()))
+            .map(|bytes| VecReader::new(bytes.to_vec()))
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))
+    }
+}
+
+impl AssetReader for ReplayAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.replay(JournalEntryKind::Asset, path)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.replay(JournalEntryKind::Meta, path)
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
@@ -0,0 +1,115 @@
+use super::{unblock, FileAssetReader};
+use crate::io::{AssetReader, AssetReaderError, PathStream, Reader};
+use alloc::{boxed::Box, vec::Vec};
+use bevy_platform_support::collections::HashSet;
+use futures_lite::StreamExt;
+use std::path::{Path, PathBuf};
+
+/// An [`AssetReader`] that looks up each path under a list of search roots in order, and reads
+/// it from the first root that has it, like the include directories of a shader compiler.
+///
+/// Unlike an [`AssetSource`](crate::io::AssetSource) with a single root, this lets assets be
+/// referred to by bare file name no matter which root holds them. Paths that are not found
+/// under any root fail with [`AssetReaderError::NotFoundInSearchPaths`], listing the roots that
+/// were searched.
+pub struct SearchPathAssetReader {
+    readers: Vec<FileAssetReader>,
+}
+
+impl SearchPathAssetReader {
+    /// Creates a reader searching `roots`, in order. Relative roots are resolved like the path
+    /// given to [`FileAssetReader::new`].
+    pub fn new<P: AsRef<Path>>(roots: impl IntoIterator<Item = P>) -> Self {
+        Self::from_readers(roots.into_iter().map(FileAssetReader::new).collect())
+    }
+
+    /// Creates a reader searching the roots of `readers`, in order, reading with their
+    /// configuration.
+    pub fn from_readers(readers: Vec<FileAssetReader>) -> Self {
+        Self { readers }
+    }
+
+    /// Returns the roots that are searched, in order.
+    pub fn search_paths(&self) -> Vec<PathBuf> {
+        self.readers
+            .iter()
+            .map(FileAssetReader::current_root)
+            .collect()
+    }
+
+    /// Returns the reader of the first root holding a file at `path`.
+    async fn find(&self, path: &Path) -> Option<&FileAssetReader> {
+        for reader in &self.readers {
+            let full_path = reader.full_path(path);
+            if unblock(move || full_path.is_file()).await {
+                return Some(reader);
+            }
+        }
+        None
+    }
+
+    fn not_found(&self, path: &Path) -> AssetReaderError {
+        AssetReaderError::NotFoundInSearchPaths {
+            path: path.to_owned(),
+            searched: self.search_paths(),
+        }
+    }
+}
+
+impl AssetReader for SearchPathAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        for reader in &self.readers {
+            match reader.read_asset(path).await {
+                Err(AssetReaderError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(self.not_found(path))
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        // the meta file is only looked for next to the asset, so that another root cannot
+        // supply the settings for it
+        match self.find(path).await {
+            Some(reader) => reader.read_meta(path).await,
+            None => Err(self.not_found(path)),
+        }
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let mut listed = HashSet::new();
+        let mut paths = Vec::new();
+        let mut found = false;
+        for reader in &self.readers {
+            let mut entries = match reader.read_directory(path).await {
+                Ok(entries) => entries,
+                Err(AssetReaderError::NotFound(_)) => continue,
+                Err(error) => return Err(error),
+            };
+            found = true;
+            while let Some(entry) = entries.next().await {
+                // an entry shadowed by an earlier root is not listed again
+                if listed.insert(entry.clone()) {
+                    paths.push(entry);
+                }
+            }
+        }
+        if !found {
+            return Err(self.not_found(path));
+        }
+        Ok(Box::new(futures_lite::stream::iter(paths)))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        for reader in &self.readers {
+            match reader.is_directory(path).await {
+                Err(AssetReaderError::NotFound(_)) => continue,
+                result => return result,
+            }
+        }
+        Err(self.not_found(path))
+    }
+}
@@ -0,0 +1,533 @@
+use super::{create_dir_all_concurrent, unblock, FileAssetWriter};
+use crate::io::{get_meta_path, AssetWriter, AssetWriterError, Writer};
+use alloc::{boxed::Box, format, string::ToString, sync::Arc};
+#[cfg(feature = "multi_threaded")]
+use async_io::Timer;
+#[cfg(feature = "multi_threaded")]
+use bevy_tasks::{IoTaskPool, Task};
+use bevy_utils::synccell::SyncCell;
+#[cfg(feature = "multi_threaded")]
+use core::time::Duration;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::AsyncWrite;
+use futures_lite::ready;
+use parking_lot::Mutex;
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+#[cfg(feature = "multi_threaded")]
+use tracing::error;
+
+/// A [`FileAssetWriter`] that writes assets and meta files to a staging directory, and only moves
+/// them into the live directory when [`StagingAssetWriter::publish`] is called.
+///
+/// This suits workloads that write the same assets over and over, such as an editor autosaving,
+/// by keeping the churn out of the live directory that readers are looking at.
+///
+/// A file is only staged once the writer returned by [`AssetWriter::write`] or
+/// [`AssetWriter::write_meta`] is closed, so a partially written file is never published, and
+/// publishing never waits for writers. A writer dropped without being closed discards its file.
+/// The staged files are taken as one batch with a single rename, so a publish moves exactly the
+/// files staged before it started.
+///
+/// The live directory is a symbolic link to its current generation. A publish builds the next
+/// generation from the batch and the files of the current one, then points the link at it with a
+/// single rename, so readers see either every file of a batch or none of them. Files are hard
+/// linked into the next generation where possible, so a publish costs a link per live file rather
+/// than a copy. File watchers resolve the link when they start watching, so a watcher on the live
+/// directory has to be restarted to see the generations published after it started.
+///
+/// Every other [`AssetWriter`] operation publishes the staged files first, then applies to the
+/// live directory.
+pub struct StagingAssetWriter {
+    incoming: FileAssetWriter,
+    live: FileAssetWriter,
+    publisher: Arc<Publisher>,
+    #[cfg(feature = "multi_threaded")]
+    _auto_publish: Option<Task<()>>,
+}
+
+impl StagingAssetWriter {
+    /// Wraps `live`, staging writes in the directory at `staging_path`, which is resolved like the
+    /// path given to [`FileAssetWriter::new`] and created if needed. Staged writes use the
+    /// [`WriteTransform`](super::WriteTransform) and buffer size of `live`.
+    ///
+    /// Files are written next to the staging directory until they are staged, and batches are
+    /// moved next to it while they are published, in directories named after it with an
+    /// `.incoming` and `.publishing` suffix. The generations of the live directory are kept next
+    /// to it, in a directory named after it with a `.generations` suffix. If the live directory is
+    /// a plain directory, the first publish moves it into its first generation, and it briefly
+    /// does not exist while it does.
+    ///
+    /// The staging directory should be on the same filesystem as the live directory, otherwise
+    /// every staged file is copied when it is published. Creating the symbolic link needs
+    /// permission to create symbolic links on Windows.
+    pub fn new<P: AsRef<Path> + core::fmt::Debug>(live: FileAssetWriter, staging_path: P) -> Self {
+        let staging_root = FileAssetWriter::new(staging_path, true).root_path;
+        let incoming = FileAssetWriter::from_root(with_suffix(&staging_root, ".incoming"), true);
+        let publisher = Arc::new(Publisher {
+            batch_root: with_suffix(&staging_root, ".publishing"),
+            staging_root,
+            generations_root: with_suffix(&live.root_path, ".generations"),
+            live_root: live.root_path.clone(),
+            staging: Mutex::new(()),
+            publishing: Mutex::new(()),
+        });
+        Self {
+            incoming,
+            live,
+            publisher,
+            #[cfg(feature = "multi_threaded")]
+            _auto_publish: None,
+        }
+    }
+
+    /// Publishes the staged files every `interval`, on a task spawned on the [`IoTaskPool`]. The
+    /// task is cancelled when this writer is dropped, and failures are logged.
+    ///
+    /// Nothing is published automatically if the [`IoTaskPool`] has not been initialized.
+    #[cfg(feature = "multi_threaded")]
+    pub fn with_auto_publish_interval(mut self, interval: Duration) -> Self {
+        let publisher = self.publisher.clone();
+        self._auto_publish = IoTaskPool::try_get().map(|pool| {
+            pool.spawn(async move {
+                loop {
+                    Timer::after(interval).await;
+                    if let Err(error) = publisher.publish().await {
+                        error!("Failed to publish staged assets: {error}");
+                    }
+                }
+            })
+        });
+        self
+    }
+
+    /// Publishes every staged asset and meta file as the next generation of the live directory,
+    /// replacing the files there, and returns how many files were published.
+    ///
+    /// Files whose writers are still open are not staged yet, so they are left for a later
+    /// publish. If publishing fails, the live directory is left as it was and the batch is
+    /// published first by the next publish.
+    pub async fn publish(&self) -> Result<usize, AssetWriterError> {
+        self.publisher.publish().await
+    }
+
+    /// Returns a writer for the file at `staged_path` (relative to the staging directory), which
+    /// writes to a file in the incoming directory and stages it when closed.
+    async fn stage(&self, staged_path: &Path) -> Result<Box<Writer>, AssetWriterError> {
+        let incoming_path = PathBuf::from(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let inner = self.incoming.write(&incoming_path).await?;
+        Ok(Box::new(StagedWriter {
+            inner: Some(self.live.wrap_writer(staged_path, inner)),
+            incoming_path: self.incoming.full_path(&incoming_path),
+            staged_path: staged_path.to_owned(),
+            publisher: self.publisher.clone(),
+            staging: None,
+            staged: false,
+        }))
+    }
+}
+
+/// Returns `path` with `suffix` appended to its last component.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+/// The state shared between a [`StagingAssetWriter`], its staged writers and its auto-publish
+/// task.
+struct Publisher {
+    staging_root: PathBuf,
+    /// Where the staging directory is renamed to when it is published, so that files staged
+    /// while a batch is being published start a fresh staging directory.
+    batch_root: PathBuf,
+    live_root: PathBuf,
+    /// Where the generations the live directory links to are kept.
+    generations_root: PathBuf,
+    /// Held while a file is staged, and while the staging directory is taken as a batch.
+    staging: Mutex<()>,
+    /// Held while publishing, so that publishes do not interleave.
+    publishing: Mutex<()>,
+}
+
+impl Publisher {
+    async fn publish(self: &Arc<Self>) -> Result<usize, AssetWriterError> {
+        let publisher = self.clone();
+        Ok(unblock(move || publisher.publish_blocking()).await?)
+    }
+
+    fn publish_blocking(&self) -> std::io::Result<usize> {
+        let _publishing = self.publishing.lock();
+        let mut published = 0;
+        loop {
+            // a batch left behind by a failed publish is older than the staged files, so it goes
+            // first
+            let resuming = self.batch_root.exists();
+            if !resuming {
+                let taken = {
+                    let _staging = self.staging.lock();
+                    std::fs::rename(&self.staging_root, &self.batch_root)
+                };
+                match taken {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(published),
+                    Err(e) => return Err(e),
+                }
+            }
+            published += self.publish_batch()?;
+            if !resuming {
+                return Ok(published);
+            }
+        }
+    }
+
+    /// Publishes the batch as the next generation of the live directory, then removes the batch.
+    fn publish_batch(&self) -> std::io::Result<usize> {
+        let current = self.current_generation()?;
+        self.remove_stale_generations(current.as_deref());
+        let next = self.generations_root.join(uuid::Uuid::new_v4().to_string());
+        // the batch is linked rather than moved, so that it is still whole if this fails
+        let published = link_tree(&self.batch_root, &next)?;
+        if published > 0 {
+            if let Some(current) = &current {
+                link_tree(current, &next)?;
+            }
+            self.point_live_at(&next)?;
+        } else {
+            std::fs::remove_dir_all(&next)?;
+        }
+        std::fs::remove_dir_all(&self.batch_root)?;
+        if published > 0 {
+            if let Some(current) = current {
+                // readers with files of the old generation open can keep it from being removed on
+                // some platforms, in which case a later publish removes it
+                let _ = std::fs::remove_dir_all(current);
+            }
+        }
+        Ok(published)
+    }
+
+    /// Returns the generation the live directory links to, first moving the live directory into
+    /// a generation of its own if it is a plain directory.
+    fn current_generation(&self) -> std::io::Result<Option<PathBuf>> {
+        match std::fs::symlink_metadata(&self.live_root) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let target = std::fs::read_link(&self.live_root)?;
+                Ok(Some(self.live_parent().join(target)))
+            }
+            Ok(_) => {
+                let first = self.generations_root.join(uuid::Uuid::new_v4().to_string());
+                create_dir_all_concurrent(&self.generations_root)?;
+                std::fs::rename(&self.live_root, &first)?;
+                self.point_live_at(&first)?;
+                Ok(Some(first))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Removes every generation other than `current`, which were left behind by failed publishes,
+    /// or could not be removed while readers had their files open.
+    fn remove_stale_generations(&self, current: Option<&Path>) {
+        let Ok(entries) = std::fs::read_dir(&self.generations_root) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if current != Some(entry.path().as_path()) {
+                let _ = std::fs::remove_dir_all(entry.path());
+            }
+        }
+    }
+
+    /// Points the live directory at `generation` by renaming a new link over it, which readers
+    /// see happen all at once.
+    fn point_live_at(&self, generation: &Path) -> std::io::Result<()> {
+        // the link is relative, so that the live directory can be moved along with its
+        // generations
+        let parent = self.live_parent();
+        let target = generation.strip_prefix(parent).unwrap_or(generation);
+        let link = with_suffix(&self.live_root, &format!(".{}.link", uuid::Uuid::new_v4()));
+        symlink_dir(target, &link)?;
+        std::fs::rename(&link, &self.live_root).inspect_err(|_| {
+            // the failed rename is the error worth reporting
+            let _ = std::fs::remove_file(&link);
+        })
+    }
+
+    /// Returns the directory relative links to generations are resolved from.
+    fn live_parent(&self) -> &Path {
+        self.live_root.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Moves the finished file at `incoming_path` into the staging directory at `staged_path`.
+    fn stage(&self, incoming_path: &Path, staged_path: &Path) -> std::io::Result<()> {
+        let target = self.staging_root.join(staged_path);
+        let _staging = self.staging.lock();
+        if let Some(parent) = target.parent() {
+            create_dir_all_concurrent(parent)?;
+        }
+        std::fs::rename(incoming_path, &target)
+    }
+}
+
+/// Recreates the directory tree at `from` under `to`, linking its files, and returns how many
+/// files were linked. Files that already exist under `to` are kept.
+fn link_tree(from: &Path, to: &Path) -> std::io::Result<usize> {
+    create_dir_all_concurrent(to)?;
+    let mut linked = 0;
+    let entries = match std::fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            linked += link_tree(&entry.path(), &target)?;
+            continue;
+        }
+        match link_or_copy(&entry.path(), &target) {
+            Ok(()) => linked += 1,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(linked)
+}
+
+/// Hard links the file at `from` to `to`, copying it instead if it cannot be linked, for example
+/// because the two are on different filesystems.
+fn link_or_copy(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::hard_link(from, to) {
+        Err(e) if e.kind() != ErrorKind::AlreadyExists => std::fs::copy(from, to).map(|_| ()),
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn symlink_dir(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink_dir(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(target, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink_dir(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(ErrorKind::Unsupported.into())
+}
+
+type StageFuture = Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>>;
+
+/// A [`Writer`] for a file in the incoming directory, which stages the file when closed.
+struct StagedWriter {
+    inner: Option<Box<Writer>>,
+    incoming_path: PathBuf,
+    staged_path: PathBuf,
+    publisher: Arc<Publisher>,
+    /// Moves the file into the staging directory, once the file is closed.
+    staging: Option<SyncCell<StageFuture>>,
+    staged: bool,
+}
+
+impl StagedWriter {
+    fn inner(&mut self) -> Pin<&mut Writer> {
+        Pin::new(self.inner.as_mut().expect("not used after being closed"))
+    }
+}
+
+impl AsyncWrite for StagedWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.inner().poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.inner().poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if this.staged {
+            return Poll::Ready(Ok(()));
+        }
+        if this.staging.is_none() {
+            let Some(inner) = &mut this.inner else {
+                return Poll::Ready(Err(std::io::Error::other(
+                    "staging the file already failed",
+                )));
+            };
+            ready!(Pin::new(inner).poll_close(cx))?;
+            // the file is closed before it is moved, since an open file cannot be renamed on
+            // every platform
+            this.inner = None;
+            let publisher = this.publisher.clone();
+            let incoming_path = this.incoming_path.clone();
+            let staged_path = this.staged_path.clone();
+            this.staging = Some(SyncCell::new(Box::pin(unblock(move || {
+                publisher.stage(&incoming_path, &staged_path)
+            }))));
+        }
+        let staging = this.staging.as_mut().expect("set above").get();
+        let result = ready!(staging.as_mut().poll(cx));
+        this.staging = None;
+        this.staged = result.is_ok();
+        Poll::Ready(result)
+    }
+}
+
+impl Drop for StagedWriter {
+    fn drop(&mut self) {
+        if !self.staged {
+            // the file was never closed or could not be staged, so it is of no use
+            drop(self.inner.take());
+            let _ = std::fs::remove_file(&self.incoming_path);
+        }
+    }
+}
+
+impl AssetWriter for StagingAssetWriter {
+    async fn write<'a>(&'a self, path: &'a Path) -> Result<Box<Writer>, AssetWriterError> {
+        self.stage(path).await
+    }
+
+    async fn write_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Writer>, AssetWriterError> {
+        self.stage(&get_meta_path(path)).await
+    }
+
+    async fn remove<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.remove(path).await
+    }
+
+    async fn remove_meta<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.remove_meta(path).await
+    }
+
+    async fn rename<'a>(
+        &'a self,
+        old_path: &'a Path,
+        new_path: &'a Path,
+    ) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.rename(old_path, new_path).await
+    }
+
+    async fn rename_meta<'a>(
+        &'a self,
+        old_path: &'a Path,
+        new_path: &'a Path,
+    ) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.rename_meta(old_path, new_path).await
+    }
+
+    async fn create_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.create_directory(path).await
+    }
+
+    async fn remove_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.remove_directory(path).await
+    }
+
+    async fn remove_empty_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.remove_empty_directory(path).await
+    }
+
+    async fn remove_assets_in_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<(), AssetWriterError> {
+        self.publish().await?;
+        self.live.remove_assets_in_directory(path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::file::tests::TempDir;
+    use bevy_tasks::block_on;
+    use futures_lite::AsyncWriteExt;
+
+    async fn stage_all(writer: &StagingAssetWriter, files: &[(&str, &str)]) {
+        for (path, text) in files {
+            let mut file = writer.write(Path::new(path)).await.unwrap();
+            file.write_all(text.as_bytes()).await.unwrap();
+            file.close().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn open_writers_do_not_block_publishing() {
+        let root = TempDir::new("staging");
+        let live = FileAssetWriter::new(root.join("live"), true);
+        let writer = StagingAssetWriter::new(live, root.join("staging"));
+        block_on(async {
+            let mut open = writer.write(Path::new("a.txt")).await.unwrap();
+            open.write_all(b"a").await.unwrap();
+            open.flush().await.unwrap();
+            stage_all(&writer, &[("b.txt", "b")]).await;
+            // this used to wait for `open` to be dropped, which never happened
+            writer.remove(Path::new("missing.txt")).await.ok();
+            let published_while_open = root.join("live/a.txt").exists();
+            open.close().await.unwrap();
+            let published = writer.publish().await.unwrap();
+            let live = (
+                std::fs::read(root.join("live/a.txt")).unwrap(),
+                std::fs::read(root.join("live/b.txt")).unwrap(),
+            );
+            assert!(!published_while_open);
+            assert_eq!(published, 1);
+            assert_eq!(live, (b"a".to_vec(), b"b".to_vec()));
+        });
+    }
+
+    #[test]
+    fn failed_publishes_leave_the_live_directory_whole() {
+        let root = TempDir::new("staging");
+        let live = FileAssetWriter::new(root.join("live"), true);
+        let writer = StagingAssetWriter::new(live, root.join("staging"));
+        let read_live = || {
+            ["a.txt", "b.txt", "c/d.txt"]
+                .map(|path| std::fs::read(root.join("live").join(path)).ok())
+        };
+        block_on(async {
+            stage_all(&writer, &[("a.txt", "1"), ("b.txt", "1"), ("c/d.txt", "1")]).await;
+            let first = writer.publish().await.unwrap();
+            let after_first = read_live();
+            // `c` cannot be both the file of the new batch and the directory of the live files,
+            // so this fails after some of the batch has been linked into the next generation
+            stage_all(&writer, &[("a.txt", "2"), ("b.txt", "2"), ("c", "2")]).await;
+            let second = writer.publish().await;
+            let after_second = read_live();
+            let generations = std::fs::read_dir(root.join("live.generations"))
+                .unwrap()
+                .count();
+            let old = Some(b"1".to_vec());
+            assert_eq!(first, 3);
+            assert_eq!(after_first, [old.clone(), old.clone(), old.clone()]);
+            assert!(second.is_err());
+            assert_eq!(after_second, [old.clone(), old.clone(), old]);
+            // the partial generation is only removed by the next publish
+            assert_eq!(generations, 2);
+        });
+    }
+}
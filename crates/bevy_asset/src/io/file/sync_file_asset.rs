@@ -8,13 +8,19 @@ use crate::io::{
 
 use alloc::{borrow::ToOwned, boxed::Box, vec::Vec};
 use core::{pin::Pin, task::Poll};
+#[cfg(unix)]
+use std::os::fd::OwnedFd;
 use std::{
     fs::{read_dir, File},
     io::{Read, Seek, Write},
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use super::{FileAssetReader, FileAssetWriter};
+use super::{
+    cap_path_stream, create_dir_all_concurrent, is_busy_error, join_root, read_stdin,
+    walk::is_meta_file, EntryStream, FileAssetReader, FileAssetWriter,
+};
 
 struct FileReader(File);
 
@@ -84,10 +90,10 @@ impl AsyncWrite for FileWriter {
     }
 }
 
-struct DirReader(Vec<PathBuf>);
+struct DirReader<T>(Vec<T>);
 
-impl Stream for DirReader {
-    type Item = PathBuf;
+impl<T: Unpin> Stream for DirReader<T> {
+    type Item = T;
 
     fn poll_next(
         self: Pin<&mut Self>,
@@ -98,46 +104,19 @@ impl Stream for DirReader {
     }
 }
 
-impl AssetReader for FileAssetReader {
-    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
-        let full_path = self.root_path.join(path);
-        match File::open(&full_path) {
-            Ok(file) => Ok(FileReader(file)),
-            Err(e) => {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    Err(AssetReaderError::NotFound(full_path))
-                } else {
-                    Err(e.into())
-                }
-            }
-        }
-    }
-
-    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
-        let meta_path = get_meta_path(path);
-        let full_path = self.root_path.join(meta_path);
-        match File::open(&full_path) {
-            Ok(file) => Ok(FileReader(file)),
-            Err(e) => {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    Err(AssetReaderError::NotFound(full_path))
-                } else {
-                    Err(e.into())
-                }
-            }
-        }
-    }
-
-    async fn read_directory<'a>(
-        &'a self,
-        path: &'a Path,
-    ) -> Result<Box<PathStream>, AssetReaderError> {
-        let full_path = self.root_path.join(path);
+impl FileAssetReader {
+    /// Lists every non-meta entry of the directory at `path`, relative to the root path, along
+    /// with any errors encountered while reading individual entries.
+    pub(super) async fn open_directory_entries(
+        &self,
+        path: &Path,
+    ) -> Result<Box<EntryStream>, AssetReaderError> {
+        let root_path = self.current_root();
+        let full_path = join_root(&root_path, path);
         match read_dir(&full_path) {
             Ok(read_dir) => {
-                let root_path = self.root_path.clone();
-                let mapped_stream = read_dir.filter_map(move |f| {
-                    f.ok().and_then(|dir_entry| {
+                let mapped_stream = read_dir.filter_map(move |f| match f {
+                    Ok(dir_entry) => {
                         let path = dir_entry.path();
                         // filter out meta files as they are not considered assets
                         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
@@ -146,10 +125,11 @@ impl AssetReader for FileAssetReader {
                             }
                         }
                         let relative_path = path.strip_prefix(&root_path).unwrap();
-                        Some(relative_path.to_owned())
-                    })
+                        Some(Ok(relative_path.to_owned()))
+                    }
+                    Err(e) => Some(Err(e.into())),
                 });
-                let read_dir: Box<PathStream> = Box::new(DirReader(mapped_stream.collect()));
+                let read_dir: Box<EntryStream> = Box::new(DirReader(mapped_stream.collect()));
                 Ok(read_dir)
             }
             Err(e) => {
@@ -162,8 +142,170 @@ impl AssetReader for FileAssetReader {
         }
     }
 
+    /// Lists every non-meta entry of the directory at `path`, relative to the root path, along
+    /// with its last modification time.
+    ///
+    /// The modification time is taken from the directory entry, which some platforms can answer
+    /// without a separate `stat` call. Entries that cannot be read, or whose modification time is
+    /// unavailable, are skipped.
+    pub async fn read_directory_with_mtime(
+        &self,
+        path: &Path,
+    ) -> Result<impl Stream<Item = (PathBuf, SystemTime)> + Unpin + Send, AssetReaderError> {
+        let root_path = self.current_root();
+        let full_path = join_root(&root_path, path);
+        let read_dir = match read_dir(&full_path) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(AssetReaderError::NotFound(full_path));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let entries = read_dir
+            .filter_map(|entry| {
+                let dir_entry = entry.ok()?;
+                let path = dir_entry.path();
+                if is_meta_file(&path) {
+                    return None;
+                }
+                let modified = dir_entry.metadata().ok()?.modified().ok()?;
+                let relative_path = path.strip_prefix(&root_path).unwrap();
+                Some((relative_path.to_owned(), modified))
+            })
+            .collect();
+        Ok(DirReader(entries))
+    }
+
+    /// Opens the file at `full_path` for reading, with the configured share mode on Windows.
+    fn open_file(&self, full_path: &Path) -> std::io::Result<File> {
+        #[cfg(feature = "test_util")]
+        if let Some(error) = self.injected_faults.take() {
+            return Err(error);
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.read(true);
+        #[cfg(windows)]
+        std::os::windows::fs::OpenOptionsExt::share_mode(
+            &mut options,
+            self.read_share_mode.flags(),
+        );
+        options.open(full_path)
+    }
+
+    /// Applies the configured decompression to an opened asset file.
+    async fn wrap_reader(&self, file: File) -> Result<Box<dyn Reader>, AssetReaderError> {
+        #[cfg(feature = "compression")]
+        let reader: Box<dyn Reader> = if self.auto_decompress {
+            Box::new(super::DecompressReader::new(FileReader(file)).await?)
+        } else {
+            Box::new(FileReader(file))
+        };
+        #[cfg(not(feature = "compression"))]
+        let reader: Box<dyn Reader> = Box::new(FileReader(file));
+        Ok(reader)
+    }
+
+    /// Reads an asset from `fd`, a file that was already opened elsewhere, for example by a sandbox
+    /// broker that does not allow opening files by path. The configured wrappers are applied as
+    /// for [`AssetReader::read`], and `fd` is closed when the returned reader is dropped.
+    ///
+    /// A raw descriptor can be turned into an [`OwnedFd`] with
+    /// [`FromRawFd::from_raw_fd`](std::os::fd::FromRawFd::from_raw_fd).
+    #[cfg(unix)]
+    pub async fn read_from_fd(&self, fd: OwnedFd) -> Result<Box<dyn Reader>, AssetReaderError> {
+        self.wrap_reader(File::from(fd)).await
+    }
+
+    /// Opens the asset file at `full_path` and applies the configured wrappers, without retrying.
+    pub(super) async fn open_reader(
+        &self,
+        full_path: PathBuf,
+    ) -> Result<Box<dyn Reader>, AssetReaderError> {
+        match self.open_file(&full_path) {
+            Ok(file) => self.wrap_reader(file).await,
+            Err(e) => Err(self.open_error(full_path, e).await),
+        }
+    }
+
+    /// Opens the asset at `path`, honoring the stdin path and the not found retry. This is
+    /// [`AssetReader::read`] with a reader that does not borrow `self`.
+    pub(super) async fn read_asset(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Reader>, AssetReaderError> {
+        if self.is_stdin_path(path) {
+            return Ok(Box::new(read_stdin()?));
+        }
+        let mut full_path = self.full_path(path);
+        let mut attempt = 0;
+        let mut busy_attempt = 0;
+        loop {
+            if self.is_shut_down() {
+                return Err(AssetReaderError::Shutdown);
+            }
+            match self.open_file(&full_path) {
+                Ok(file) => return self.wrap_reader(file).await,
+                // the root path may have moved, in which case the read is retried at the new one
+                Err(_) if self.reresolve_root() => full_path = self.full_path(path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => match self.not_found_retry {
+                    // the file may be mid-way through an atomic rename, so give the writer a
+                    // chance to finish before reporting it as missing
+                    Some(retry) if attempt < retry.attempts => {
+                        attempt += 1;
+                        std::thread::sleep(retry.delay);
+                    }
+                    _ => return Err(self.open_error(full_path, e).await),
+                },
+                // an editor may be saving the file, which usually takes a moment at most
+                Err(e) if is_busy_error(&e) => match self.busy_retry {
+                    Some(retry) if busy_attempt < retry.attempts => {
+                        busy_attempt += 1;
+                        std::thread::sleep(retry.delay);
+                    }
+                    _ => return Err(self.open_error(full_path, e).await),
+                },
+                Err(e) => return Err(self.open_error(full_path, e).await),
+            }
+        }
+    }
+}
+
+impl AssetReader for FileAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.read_asset(path).await
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        let meta_path = get_meta_path(path);
+        let mut full_path = self.full_path(&meta_path);
+        if self.is_stdin_path(path) {
+            return Err(AssetReaderError::NotFound(full_path));
+        }
+        if self.is_shut_down() {
+            return Err(AssetReaderError::Shutdown);
+        }
+        let mut result = self.open_file(&full_path);
+        if result.is_err() && self.reresolve_root() {
+            full_path = self.full_path(&meta_path);
+            result = self.open_file(&full_path);
+        }
+        match result {
+            Ok(file) => Ok(FileReader(file)),
+            Err(e) => Err(self.open_error(full_path, e).await),
+        }
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let entries = self.open_directory_entries(path).await?;
+        let read_dir: Box<PathStream> = Box::new(entries.filter_map(Result::ok));
+        Ok(cap_path_stream(read_dir, path, self.max_directory_entries))
+    }
+
     async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         let metadata = full_path
             .metadata()
             .map_err(|_e| AssetReaderError::NotFound(path.to_owned()))?;
@@ -171,55 +313,81 @@ impl AssetReader for FileAssetReader {
     }
 }
 
+impl FileAssetWriter {
+    /// Creates the file at `path` and returns a writer for it, failing with
+    /// [`AssetWriterError::AlreadyExists`] if it is already present. Checking for the file and
+    /// creating it is a single atomic operation, so this can be used as a lock or marker file to
+    /// coordinate processes sharing the asset directory.
+    pub async fn create_exclusive(&self, path: &Path) -> Result<Box<Writer>, AssetWriterError> {
+        let full_path = self.full_path(path);
+        if let Some(parent) = full_path.parent() {
+            create_dir_all_concurrent(parent)?;
+        }
+        let file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&full_path)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(AssetWriterError::AlreadyExists(full_path));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let writer: Box<Writer> = Box::new(FileWriter(file));
+        Ok(self.wrap_writer(path, writer))
+    }
+}
+
 impl AssetWriter for FileAssetWriter {
     async fn write<'a>(&'a self, path: &'a Path) -> Result<Box<Writer>, AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         if let Some(parent) = full_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_dir_all_concurrent(parent)?;
         }
         let file = File::create(&full_path)?;
         let writer: Box<Writer> = Box::new(FileWriter(file));
-        Ok(writer)
+        Ok(self.wrap_writer(path, writer))
     }
 
     async fn write_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Writer>, AssetWriterError> {
         let meta_path = get_meta_path(path);
-        let full_path = self.root_path.join(meta_path);
+        let full_path = self.full_path(&meta_path);
         if let Some(parent) = full_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_dir_all_concurrent(parent)?;
         }
         let file = File::create(&full_path)?;
         let writer: Box<Writer> = Box::new(FileWriter(file));
-        Ok(writer)
+        Ok(self.wrap_writer(&meta_path, writer))
     }
 
     async fn remove<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         std::fs::remove_file(full_path)?;
         Ok(())
     }
 
     async fn remove_meta<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
         let meta_path = get_meta_path(path);
-        let full_path = self.root_path.join(meta_path);
+        let full_path = self.full_path(&meta_path);
         std::fs::remove_file(full_path)?;
         Ok(())
     }
 
     async fn create_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
-        std::fs::create_dir_all(full_path)?;
+        let full_path = self.full_path(path);
+        create_dir_all_concurrent(&full_path)?;
         Ok(())
     }
 
     async fn remove_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         std::fs::remove_dir_all(full_path)?;
         Ok(())
     }
 
     async fn remove_empty_directory<'a>(&'a self, path: &'a Path) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         std::fs::remove_dir(full_path)?;
         Ok(())
     }
@@ -228,9 +396,9 @@ impl AssetWriter for FileAssetWriter {
         &'a self,
         path: &'a Path,
     ) -> Result<(), AssetWriterError> {
-        let full_path = self.root_path.join(path);
+        let full_path = self.full_path(path);
         std::fs::remove_dir_all(&full_path)?;
-        std::fs::create_dir_all(&full_path)?;
+        create_dir_all_concurrent(&full_path)?;
         Ok(())
     }
 
@@ -239,10 +407,10 @@ impl AssetWriter for FileAssetWriter {
         old_path: &'a Path,
         new_path: &'a Path,
     ) -> Result<(), AssetWriterError> {
-        let full_old_path = self.root_path.join(old_path);
-        let full_new_path = self.root_path.join(new_path);
+        let full_old_path = self.full_path(old_path);
+        let full_new_path = self.full_path(new_path);
         if let Some(parent) = full_new_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_dir_all_concurrent(parent)?;
         }
         std::fs::rename(full_old_path, full_new_path)?;
         Ok(())
@@ -255,10 +423,10 @@ impl AssetWriter for FileAssetWriter {
     ) -> Result<(), AssetWriterError> {
         let old_meta_path = get_meta_path(old_path);
         let new_meta_path = get_meta_path(new_path);
-        let full_old_path = self.root_path.join(old_meta_path);
-        let full_new_path = self.root_path.join(new_meta_path);
+        let full_old_path = self.full_path(&old_meta_path);
+        let full_new_path = self.full_path(&new_meta_path);
         if let Some(parent) = full_new_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_dir_all_concurrent(parent)?;
         }
         std::fs::rename(full_old_path, full_new_path)?;
         Ok(())
@@ -0,0 +1,115 @@
+use crate::io::{AsyncSeekForward, Reader};
+use alloc::{boxed::Box, sync::Arc};
+use async_io::Timer;
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures_io::AsyncRead;
+use futures_lite::ready;
+use parking_lot::Mutex;
+use std::time::Instant;
+
+/// A token bucket limiting the aggregate rate at which bytes are read, shared by every reader
+/// opened by a [`FileAssetReader`](super::FileAssetReader) and its clones.
+///
+/// The bucket holds up to one second's worth of bytes. Reads are allowed while it is not empty
+/// and the bytes they return are taken from it afterwards, so a read can leave the bucket in
+/// debt, which later reads wait out.
+pub(super) struct ReadRateLimit {
+    bytes_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    available: f64,
+    refilled_at: Instant,
+}
+
+impl ReadRateLimit {
+    pub(super) fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1) as f64;
+        Self {
+            bytes_per_sec,
+            bucket: Mutex::new(Bucket {
+                available: bytes_per_sec,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Returns how long to wait before reading, or `None` if a read can start right away.
+    fn wait_time(&self) -> Option<Duration> {
+        let mut bucket = self.bucket.lock();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.available =
+            (bucket.available + elapsed * self.bytes_per_sec).min(self.bytes_per_sec);
+        bucket.refilled_at = now;
+        if bucket.available >= 1.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - bucket.available) / self.bytes_per_sec,
+            ))
+        }
+    }
+
+    fn consume(&self, bytes: usize) {
+        self.bucket.lock().available -= bytes as f64;
+    }
+}
+
+/// A [`Reader`] whose reads are throttled by a [`ReadRateLimit`].
+pub(super) struct RateLimitedReader {
+    inner: Box<dyn Reader>,
+    limit: Arc<ReadRateLimit>,
+    delay: Option<Timer>,
+}
+
+impl RateLimitedReader {
+    pub(super) fn new(inner: Box<dyn Reader>, limit: Arc<ReadRateLimit>) -> Self {
+        Self {
+            inner,
+            limit,
+            delay: None,
+        }
+    }
+}
+
+impl AsyncRead for RateLimitedReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(delay) = &mut this.delay {
+                ready!(Pin::new(delay).poll(cx));
+                this.delay = None;
+            }
+            match this.limit.wait_time() {
+                Some(wait) => this.delay = Some(Timer::after(wait)),
+                None => break,
+            }
+        }
+        let read = ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        this.limit.consume(read);
+        Poll::Ready(Ok(read))
+    }
+}
+
+impl AsyncSeekForward for RateLimitedReader {
+    fn poll_seek_forward(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        offset: u64,
+    ) -> Poll<std::io::Result<u64>> {
+        Pin::new(&mut self.inner).poll_seek_forward(cx, offset)
+    }
+}
+
+impl Reader for RateLimitedReader {}
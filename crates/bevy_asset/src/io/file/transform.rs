@@ -0,0 +1,83 @@
+use crate::io::Writer;
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_io::AsyncWrite;
+use futures_lite::ready;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A function that rewrites the bytes of an asset before they are written to disk. It receives the
+/// path being written (relative to the writer's root) and the bytes, and returns the bytes to write.
+pub type WriteTransform = dyn Fn(&Path, Vec<u8>) -> Vec<u8> + Send + Sync;
+
+/// A [`Writer`] that buffers everything written to it and passes it through a [`WriteTransform`]
+/// as a whole when closed. Dropping it without closing it discards the buffered bytes.
+pub(super) struct TransformWriter {
+    path: PathBuf,
+    transform: Arc<WriteTransform>,
+    inner: Box<Writer>,
+    buffer: Vec<u8>,
+    /// The transformed bytes, once closing has started.
+    transformed: Option<Vec<u8>>,
+    written: usize,
+}
+
+impl TransformWriter {
+    pub(super) fn new(path: PathBuf, transform: Arc<WriteTransform>, inner: Box<Writer>) -> Self {
+        Self {
+            path,
+            transform,
+            inner,
+            buffer: Vec::new(),
+            transformed: None,
+            written: 0,
+        }
+    }
+}
+
+impl AsyncWrite for TransformWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut().buffer.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        // the transform needs the whole file, so buffered bytes are held back until it is closed
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let transformed = this
+            .transformed
+            .get_or_insert_with(|| (this.transform)(&this.path, mem::take(&mut this.buffer)));
+        while this.written < transformed.len() {
+            let n = ready!(Pin::new(&mut this.inner).poll_write(cx, &transformed[this.written..]))?;
+            if n == 0 {
+                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+            }
+            this.written += n;
+        }
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
+
+impl Drop for TransformWriter {
+    fn drop(&mut self) {
+        if self.transformed.is_none() && !self.buffer.is_empty() {
+            warn!(
+                "{} was dropped without being closed, discarding {} buffered bytes",
+                self.path.display(),
+                self.buffer.len()
+            );
+        }
+    }
+}
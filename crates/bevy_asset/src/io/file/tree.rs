@@ -0,0 +1,174 @@
+use super::{walk::walk_files, FileAssetReader};
+use crate::io::AssetReaderError;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// How [`FileAssetReader::diff_trees`] decides whether a file present in both trees has changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TreeComparison {
+    /// Files differ if their sizes or last modification times differ. This only reads metadata,
+    /// but reports copies with fresh modification times as changed.
+    #[default]
+    SizeAndModified,
+    /// Files differ if their sizes or contents differ. Files of equal size are read in full.
+    Contents,
+}
+
+/// The differences between two asset trees.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeDiff {
+    /// Files that only exist in the other tree.
+    pub added: Vec<PathBuf>,
+    /// Files that only exist in the base tree.
+    pub removed: Vec<PathBuf>,
+    /// Files that exist in both trees but differ.
+    pub changed: Vec<PathBuf>,
+}
+
+impl TreeDiff {
+    /// Returns true if the trees are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// A file recorded in a [`TreeSnapshot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotEntry {
+    /// The path of the file, relative to the root path of the reader.
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The last modification time of the file, if the platform provides it.
+    pub modified: Option<SystemTime>,
+}
+
+/// The structure of an asset tree at one point in time, taken by [`FileAssetReader::snapshot`]
+/// to be compared with a later snapshot of the same tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeSnapshot {
+    entries: Vec<SnapshotEntry>,
+}
+
+impl TreeSnapshot {
+    /// Returns the files in the snapshot, sorted by path.
+    pub fn entries(&self) -> &[SnapshotEntry] {
+        &self.entries
+    }
+
+    /// Compares this snapshot (the base) with `other`, reporting files as added (only in
+    /// `other`), removed (only in the base) or changed, if their sizes or modification times
+    /// differ. This is [`TreeComparison::SizeAndModified`] applied to the recorded metadata.
+    pub fn diff(&self, other: &TreeSnapshot) -> TreeDiff {
+        let diff = diff_sorted(
+            &self.entries,
+            &other.entries,
+            |entry| &entry.path,
+            |base, other| Ok(base.size != other.size || base.modified != other.modified),
+        );
+        diff.expect("comparing recorded metadata cannot fail")
+    }
+}
+
+impl FileAssetReader {
+    /// Records the path, size and modification time of every file in the directory at `path`,
+    /// recursively, for comparison with a later snapshot using [`TreeSnapshot::diff`]. Meta files
+    /// are recorded like any other file.
+    ///
+    /// Only metadata is read, in a single walk performed with blocking filesystem calls.
+    pub fn snapshot(&self, path: &Path) -> Result<TreeSnapshot, AssetReaderError> {
+        let entries = walk_files(&self.current_root(), path, true)?
+            .into_iter()
+            .map(|file| SnapshotEntry {
+                size: file.metadata.len(),
+                modified: file.metadata.modified().ok(),
+                path: file.path,
+            })
+            .collect();
+        Ok(TreeSnapshot { entries })
+    }
+
+    /// Compares the directory at `path` in this reader's tree (the base) with the same directory
+    /// in `other`'s tree, reporting files as added (only in `other`), removed (only in the base)
+    /// or changed according to `comparison`. Meta files are compared like any other file.
+    ///
+    /// Paths in the returned [`TreeDiff`] are relative to the root paths of the readers, and are
+    /// sorted. The walks are performed with blocking filesystem calls.
+    pub fn diff_trees(
+        &self,
+        other: &FileAssetReader,
+        path: &Path,
+        comparison: TreeComparison,
+    ) -> Result<TreeDiff, AssetReaderError> {
+        let (base_root, other_root) = (self.current_root(), other.current_root());
+        let base_files = walk_files(&base_root, path, true)?;
+        let other_files = walk_files(&other_root, path, true)?;
+        diff_sorted(
+            &base_files,
+            &other_files,
+            |file| &file.path,
+            |base, other| {
+                if base.metadata.len() != other.metadata.len() {
+                    return Ok(true);
+                }
+                Ok(match comparison {
+                    TreeComparison::SizeAndModified => {
+                        base.metadata.modified().ok() != other.metadata.modified().ok()
+                    }
+                    TreeComparison::Contents => {
+                        std::fs::read(base_root.join(&base.path))?
+                            != std::fs::read(other_root.join(&other.path))?
+                    }
+                })
+            },
+        )
+    }
+}
+
+/// Diffs two lists of entries sorted by path, using `is_changed` to compare entries present in
+/// both.
+pub(super) fn diff_sorted<T>(
+    base: &[T],
+    other: &[T],
+    path: impl Fn(&T) -> &PathBuf,
+    mut is_changed: impl FnMut(&T, &T) -> Result<bool, AssetReaderError>,
+) -> Result<TreeDiff, AssetReaderError> {
+    let mut diff = TreeDiff::default();
+    let (mut base, mut other) = (base.iter().peekable(), other.iter().peekable());
+    loop {
+        match (base.peek(), other.peek()) {
+            (Some(base_entry), Some(other_entry)) => {
+                match path(base_entry).cmp(path(other_entry)) {
+                    Ordering::Less => {
+                        diff.removed.push(path(base_entry).clone());
+                        base.next();
+                    }
+                    Ordering::Greater => {
+                        diff.added.push(path(other_entry).clone());
+                        other.next();
+                    }
+                    Ordering::Equal => {
+                        if is_changed(base_entry, other_entry)? {
+                            diff.changed.push(path(base_entry).clone());
+                        }
+                        base.next();
+                        other.next();
+                    }
+                }
+            }
+            (Some(base_entry), None) => {
+                diff.removed.push(path(base_entry).clone());
+                base.next();
+            }
+            (None, Some(other_entry)) => {
+                diff.added.push(path(other_entry).clone());
+                other.next();
+            }
+            (None, None) => return Ok(diff),
+        }
+    }
+}
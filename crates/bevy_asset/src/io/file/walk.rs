@@ -0,0 +1,68 @@
+use crate::{io::AssetReaderError, LoadPriority, SharedDescriptorLimit};
+use alloc::{borrow::ToOwned, vec, vec::Vec};
+use std::{
+    fs::Metadata,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+/// A file found by [`walk_files`].
+pub(super) struct WalkedFile {
+    /// The path of the file, relative to the root path of the walk.
+    pub(super) path: PathBuf,
+    /// The metadata of the file, following symbolic links.
+    pub(super) metadata: Metadata,
+}
+
+/// Returns true if `path` is a meta file, which is not considered an asset.
+pub(super) fn is_meta_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("meta"))
+}
+
+/// Recursively lists every file in the directory at `root_path.join(dir)`, sorted by path. Meta
+/// files are only included if `include_meta` is true.
+pub(super) fn walk_files(
+    root_path: &Path,
+    dir: &Path,
+    include_meta: bool,
+) -> Result<Vec<WalkedFile>, AssetReaderError> {
+    walk_files_within(root_path, dir, include_meta, None)
+}
+
+/// Like [`walk_files`], but holds a permit of `limit` while each directory is open, blocking
+/// until one is available. Directories are read one at a time, so the walk holds at most one.
+pub(super) fn walk_files_within(
+    root_path: &Path,
+    dir: &Path,
+    include_meta: bool,
+    limit: Option<&SharedDescriptorLimit>,
+) -> Result<Vec<WalkedFile>, AssetReaderError> {
+    let full_path = root_path.join(dir);
+    let mut files = Vec::new();
+    let mut pending = vec![full_path.clone()];
+    while let Some(dir) = pending.pop() {
+        let _permit =
+            limit.map(|shared| bevy_tasks::block_on(shared.limit.acquire(LoadPriority::Normal)));
+        let entries = std::fs::read_dir(&dir).map_err(|e| {
+            if e.kind() == ErrorKind::NotFound && dir == full_path {
+                AssetReaderError::NotFound(full_path.clone())
+            } else {
+                e.into()
+            }
+        })?;
+        for entry in entries {
+            let path = entry?.path();
+            let metadata = path.metadata()?;
+            if metadata.is_dir() {
+                pending.push(path);
+            } else if include_meta || !is_meta_file(&path) {
+                let path = path.strip_prefix(root_path).unwrap().to_owned();
+                files.push(WalkedFile { path, metadata });
+            }
+        }
+    }
+    files.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
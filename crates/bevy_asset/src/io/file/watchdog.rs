@@ -0,0 +1,68 @@
+use crate::io::{AsyncSeekForward, Reader, StackFuture, STACK_FUTURE_SIZE};
+use alloc::{boxed::Box, vec::Vec};
+use async_io::Timer;
+use bevy_tasks::{IoTaskPool, Task};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures_io::AsyncRead;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// A [`Reader`] that logs a warning naming its path if it is still open after a threshold.
+///
+/// The warning is issued by a task spawned on the [`IoTaskPool`], which is cancelled when the
+/// reader is dropped, so it fires even if the read is stuck and never completes.
+pub(super) struct SlowReadWatchdog {
+    inner: Box<dyn Reader>,
+    _watchdog: Option<Task<()>>,
+}
+
+impl SlowReadWatchdog {
+    pub(super) fn new(inner: Box<dyn Reader>, path: PathBuf, threshold: Duration) -> Self {
+        let watchdog = IoTaskPool::try_get().map(|pool| {
+            pool.spawn(async move {
+                Timer::after(threshold).await;
+                warn!(
+                    "Reading {} has taken longer than {threshold:?}. The file may be on a slow or unresponsive mount.",
+                    path.display()
+                );
+            })
+        });
+        Self {
+            inner,
+            _watchdog: watchdog,
+        }
+    }
+}
+
+impl AsyncRead for SlowReadWatchdog {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncSeekForward for SlowReadWatchdog {
+    fn poll_seek_forward(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        offset: u64,
+    ) -> Poll<std::io::Result<u64>> {
+        Pin::new(&mut self.inner).poll_seek_forward(cx, offset)
+    }
+}
+
+impl Reader for SlowReadWatchdog {
+    fn read_to_end<'a>(
+        &'a mut self,
+        buf: &'a mut Vec<u8>,
+    ) -> StackFuture<'a, std::io::Result<usize>, STACK_FUTURE_SIZE> {
+        self.inner.read_to_end(buf)
+    }
+}
@@ -21,7 +21,7 @@ mod source;
 pub use futures_lite::AsyncWriteExt;
 pub use source::*;
 
-use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
 use bevy_tasks::{BoxedFuture, ConditionalSendFuture};
 use core::future::Future;
 use core::{
@@ -49,6 +49,41 @@ pub enum AssetReaderError {
     /// If the request fails before getting a status code (e.g. request timeout, interrupted connection, etc), expect [`AssetReaderError::Io`].
     #[error("Encountered HTTP status {0:?} when loading asset")]
     HttpError(u16),
+
+    /// The path is a symbolic link whose target does not exist.
+    #[error("Symbolic link {} points to missing target {}", link.display(), target.display())]
+    DanglingSymlink {
+        /// The path of the symbolic link.
+        link: PathBuf,
+        /// The missing path the link points to.
+        target: PathBuf,
+    },
+
+    /// The reader is shutting down, or the executor running its reads has stopped and dropped the
+    /// task opening the file, so the read was abandoned.
+    #[error("The asset reader is shutting down")]
+    Shutdown,
+
+    /// The path was not found under any of the roots of a search path reader.
+    #[error("Path {} not found in any of the search paths {searched:?}", path.display())]
+    NotFoundInSearchPaths {
+        /// The path that was searched for.
+        path: PathBuf,
+        /// The roots that were searched, in order.
+        searched: Vec<PathBuf>,
+    },
+
+    /// Fewer bytes were read from the asset than it was expected to hold, most likely because
+    /// another process truncated it during the read.
+    #[error("Asset {} was truncated during the read: expected {expected} bytes, read {read}", path.display())]
+    TruncatedDuringRead {
+        /// The path of the asset.
+        path: PathBuf,
+        /// The number of bytes the asset was expected to hold.
+        expected: u64,
+        /// The number of bytes that were read.
+        read: u64,
+    },
 }
 
 impl PartialEq for AssetReaderError {
@@ -59,6 +94,33 @@ impl PartialEq for AssetReaderError {
             (Self::NotFound(path), Self::NotFound(other_path)) => path == other_path,
             (Self::Io(error), Self::Io(other_error)) => error.kind() == other_error.kind(),
             (Self::HttpError(code), Self::HttpError(other_code)) => code == other_code,
+            (
+                Self::DanglingSymlink { link, target },
+                Self::DanglingSymlink {
+                    link: other_link,
+                    target: other_target,
+                },
+            ) => link == other_link && target == other_target,
+            (Self::Shutdown, Self::Shutdown) => true,
+            (
+                Self::NotFoundInSearchPaths { path, searched },
+                Self::NotFoundInSearchPaths {
+                    path: other_path,
+                    searched: other_searched,
+                },
+            ) => path == other_path && searched == other_searched,
+            (
+                Self::TruncatedDuringRead {
+                    path,
+                    expected,
+                    read,
+                },
+                Self::TruncatedDuringRead {
+                    path: other_path,
+                    expected: other_expected,
+                    read: other_read,
+                },
+            ) => path == other_path && expected == other_expected && read == other_read,
             _ => false,
         }
     }
@@ -184,6 +246,53 @@ impl Reader for Box<dyn Reader + '_> {
     }
 }
 
+/// Generates a [`ReaderExt`] method reading an integer in the given byte order.
+macro_rules! read_int {
+    ($name:ident, $ty:ty, $from_bytes:ident, $order:literal) => {
+        #[doc = concat!("Reads a ", $order, " `", stringify!($ty), "`.")]
+        fn $name(&mut self) -> impl Future<Output = std::io::Result<$ty>> + Send + '_ {
+            async move { Ok(<$ty>::$from_bytes(self.read_array().await?)) }
+        }
+    };
+}
+
+/// Extension trait for [`Reader`] with helpers for parsing fixed-size binary headers.
+///
+/// Integers are read without any intermediate allocation.
+pub trait ReaderExt: Reader {
+    /// Reads exactly `N` bytes into an array.
+    fn read_array<const N: usize>(
+        &mut self,
+    ) -> impl Future<Output = std::io::Result<[u8; N]>> + Send + '_ {
+        async move {
+            let mut bytes = [0; N];
+            futures_lite::AsyncReadExt::read_exact(self, &mut bytes).await?;
+            Ok(bytes)
+        }
+    }
+
+    /// Reads exactly `n` bytes into a new [`Vec`].
+    fn read_exact_n(
+        &mut self,
+        n: usize,
+    ) -> impl Future<Output = std::io::Result<Vec<u8>>> + Send + '_ {
+        async move {
+            let mut bytes = vec![0; n];
+            futures_lite::AsyncReadExt::read_exact(self, &mut bytes).await?;
+            Ok(bytes)
+        }
+    }
+
+    read_int!(read_u16_le, u16, from_le_bytes, "little-endian");
+    read_int!(read_u16_be, u16, from_be_bytes, "big-endian");
+    read_int!(read_u32_le, u32, from_le_bytes, "little-endian");
+    read_int!(read_u32_be, u32, from_be_bytes, "big-endian");
+    read_int!(read_u64_le, u64, from_le_bytes, "little-endian");
+    read_int!(read_u64_be, u64, from_be_bytes, "big-endian");
+}
+
+impl<R: Reader + ?Sized> ReaderExt for R {}
+
 /// A future that returns a value or an [`AssetReaderError`]
 pub trait AssetReaderFuture:
     ConditionalSendFuture<Output = Result<Self::Value, AssetReaderError>>
@@ -336,6 +445,15 @@ pub enum AssetWriterError {
     /// Encountered an I/O error while loading an asset.
     #[error("encountered an io error while loading asset: {0}")]
     Io(#[from] std::io::Error),
+    /// The bytes read back after writing an asset did not match the bytes that were written.
+    #[error("the contents of {} did not match the written bytes when read back", _0.display())]
+    VerificationFailed(PathBuf),
+    /// The file could not be created because it already exists.
+    #[error("the file {} already exists", _0.display())]
+    AlreadyExists(PathBuf),
+    /// The file was not changed because its contents did not match what was expected.
+    #[error("the contents of {} did not match the expected hash", _0.display())]
+    Conflict(PathBuf),
 }
 
 /// Preforms write operations on an asset storage. [`AssetWriter`] exposes a "virtual filesystem"
@@ -413,7 +531,7 @@ pub trait AssetWriter: Send + Sync + 'static {
         async {
             let mut writer = self.write(path).await?;
             writer.write_all(bytes).await?;
-            writer.flush().await?;
+            writer.close().await?;
             Ok(())
         }
     }
@@ -426,7 +544,7 @@ pub trait AssetWriter: Send + Sync + 'static {
         async {
             let mut meta_writer = self.write_meta(path).await?;
             meta_writer.write_all(bytes).await?;
-            meta_writer.flush().await?;
+            meta_writer.close().await?;
             Ok(())
         }
     }
@@ -325,15 +325,27 @@ impl AssetPlugin {
 impl Plugin for AssetPlugin {
     fn build(&self, app: &mut App) {
         let embedded = EmbeddedAssetRegistry::default();
+        #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+        let file_io_config = app
+            .world()
+            .get_resource::<io::file::FileIoConfig>()
+            .cloned();
         {
             let mut sources = app
                 .world_mut()
                 .get_resource_or_init::<AssetSourceBuilders>();
-            sources.init_default_source(
-                &self.file_path,
-                (!matches!(self.mode, AssetMode::Unprocessed))
-                    .then_some(self.processed_file_path.as_str()),
-            );
+            let processed_file_path = (!matches!(self.mode, AssetMode::Unprocessed))
+                .then_some(self.processed_file_path.as_str());
+            #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+            if let Some(config) = &file_io_config {
+                if sources.get_mut(AssetSourceId::Default).is_none() {
+                    sources.insert(
+                        AssetSourceId::Default,
+                        config.default_source(&self.file_path, processed_file_path),
+                    );
+                }
+            }
+            sources.init_default_source(&self.file_path, processed_file_path);
             embedded.register_source(&mut sources);
         }
         {
@@ -385,6 +397,12 @@ impl Plugin for AssetPlugin {
                 }
             }
         }
+        #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+        if let Some(limit) = file_io_config.and_then(|config| config.descriptor_limit) {
+            app.world()
+                .resource::<AssetServer>()
+                .set_descriptor_limit(limit);
+        }
         app.insert_resource(embedded)
             .init_asset::<LoadedFolder>()
             .init_asset::<LoadedUntypedAsset>()
@@ -635,11 +653,12 @@ mod tests {
         io::{
             gated::{GateOpener, GatedReader},
             memory::{Dir, MemoryAssetReader},
-            AssetReader, AssetReaderError, AssetSource, AssetSourceId, Reader,
+            AssetReader, AssetReaderError, AssetSource, AssetSourceBuilders, AssetSourceId, Reader,
         },
         loader::{AssetLoader, LoadContext},
         Asset, AssetApp, AssetEvent, AssetId, AssetLoadError, AssetLoadFailedEvent, AssetPath,
-        AssetPlugin, AssetServer, Assets, DuplicateLabelAssetError, LoadState,
+        AssetPlugin, AssetServer, AssetServerMode, Assets, DirectoryHandleLimit,
+        DuplicateLabelAssetError, LoadState, SharedDescriptorLimit, UncountedAssets,
     };
     use alloc::{
         boxed::Box,
@@ -1823,6 +1842,220 @@ mod tests {
         });
     }
 
+    #[test]
+    fn uncounted_assets_load_while_the_descriptor_limit_is_exhausted() {
+        let mut app = App::new();
+
+        let dir = Dir::default();
+        for path in ["version.cool.ron", "counted.cool.ron"] {
+            dir.insert_asset_text(
+                Path::new(path),
+                r#"(
+    text: "a",
+    dependencies: [],
+    embedded_dependencies: [],
+    sub_texts: [],
+)"#,
+            );
+        }
+
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+        ));
+
+        app.init_asset::<CoolText>()
+            .init_asset::<SubText>()
+            .register_asset_loader(CoolTextLoader);
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        asset_server.set_descriptor_limit(1);
+        asset_server.set_uncounted_assets(UncountedAssets {
+            paths: vec![AssetPath::from("version.cool.ron")],
+            ..Default::default()
+        });
+        let reservation = asset_server.try_reserve_descriptor().unwrap();
+        let uncounted = asset_server.load::<CoolText>("version.cool.ron");
+        let counted = asset_server.load::<CoolText>("counted.cool.ron");
+
+        run_app_until(&mut app, |_world| {
+            match asset_server.load_state(&uncounted) {
+                LoadState::Loading => None,
+                LoadState::Loaded => Some(()),
+                state => panic!("Unexpected asset state: {state:?}"),
+            }
+        });
+        assert!(asset_server.load_state(&counted).is_loading());
+
+        drop(reservation);
+        run_app_until(&mut app, |_world| match asset_server.load_state(&counted) {
+            LoadState::Loading => None,
+            LoadState::Loaded => Some(()),
+            state => panic!("Unexpected asset state: {state:?}"),
+        });
+    }
+
+    #[test]
+    fn servers_sharing_a_descriptor_limit_share_its_permits() {
+        let new_server = || {
+            let mut builders = AssetSourceBuilders::default();
+            builders.insert(
+                AssetSourceId::Default,
+                AssetSource::build().with_reader(|| {
+                    Box::new(MemoryAssetReader {
+                        root: Dir::default(),
+                    })
+                }),
+            );
+            AssetServer::new(
+                builders.build_sources(false, false),
+                AssetServerMode::Unprocessed,
+                false,
+            )
+        };
+        let (first, second) = (new_server(), new_server());
+        first.set_descriptor_limit(1);
+        second.adopt_descriptor_limit(&first.shared_descriptor_limit());
+
+        let reservation = first.try_reserve_descriptor().unwrap();
+        assert!(second.try_reserve_descriptor().is_none());
+        drop(reservation);
+        assert!(second.try_reserve_descriptor().is_some());
+
+        second.set_descriptor_limit(3);
+        assert_eq!(first.descriptor_limit(), 3);
+        assert_eq!(first.shared_descriptor_limit().available(), 3);
+    }
+
+    #[test]
+    fn counted_directories_wait_for_a_descriptor_permit() {
+        let mut app = App::new();
+
+        let dir = Dir::default();
+        dir.insert_asset_text(
+            Path::new("folder/a.cool.ron"),
+            r#"(
+    text: "a",
+    dependencies: [],
+    embedded_dependencies: [],
+    sub_texts: [],
+)"#,
+        );
+
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+        ));
+
+        app.init_asset::<CoolText>()
+            .init_asset::<SubText>()
+            .register_asset_loader(CoolTextLoader);
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        asset_server.set_descriptor_limit(1);
+        asset_server.set_directory_handle_limit(DirectoryHandleLimit::Loads);
+        let reservation = asset_server.try_reserve_descriptor().unwrap();
+        let handle = asset_server.load_folder("folder");
+        for _ in 0..100 {
+            app.update();
+        }
+        assert!(asset_server.load_state(&handle).is_loading());
+
+        // the directory releases its permit before the asset in it is loaded with the only one
+        drop(reservation);
+        run_app_until(&mut app, |world| {
+            let folder = get::<LoadedFolder>(world, handle.id())?;
+            assert_eq!(folder.handles.len(), 1);
+            Some(())
+        });
+    }
+
+    #[test]
+    fn sources_with_their_own_descriptor_limit_do_not_wait_for_the_server() {
+        let mut app = App::new();
+
+        let dir = Dir::default();
+        dir.insert_asset_text(
+            Path::new("a.cool.ron"),
+            r#"(
+    text: "a",
+    dependencies: [],
+    embedded_dependencies: [],
+    sub_texts: [],
+)"#,
+        );
+
+        let remote = dir.clone();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        )
+        .register_asset_source(
+            "remote",
+            AssetSource::build().with_reader(move || {
+                Box::new(MemoryAssetReader {
+                    root: remote.clone(),
+                })
+            }),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+        ));
+
+        app.init_asset::<CoolText>()
+            .init_asset::<SubText>()
+            .register_asset_loader(CoolTextLoader);
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        asset_server.set_descriptor_limit(1);
+        let remote_limit = SharedDescriptorLimit::new(1);
+        asset_server.set_source_descriptor_limit("remote", Some(&remote_limit));
+        let _reservation = asset_server.try_reserve_descriptor().unwrap();
+        let handle = asset_server.load::<CoolText>("remote://a.cool.ron");
+
+        run_app_until(&mut app, |_world| match asset_server.load_state(&handle) {
+            LoadState::Loading => None,
+            LoadState::Loaded => Some(()),
+            state => panic!("Unexpected asset state: {state:?}"),
+        });
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
+    #[test]
+    fn file_io_config_sets_the_descriptor_limit() {
+        let mut app = App::new();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(|| {
+                Box::new(MemoryAssetReader {
+                    root: Dir::default(),
+                })
+            }),
+        )
+        .insert_resource(crate::io::file::FileIoConfig {
+            descriptor_limit: Some(7),
+            ..Default::default()
+        })
+        .add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()));
+
+        assert_eq!(app.world().resource::<AssetServer>().descriptor_limit(), 7);
+    }
+
     // This test is not checking a requirement, but documenting a current limitation. We simply are
     // not capable of loading subassets when doing nested immediate loads.
     #[test]
@@ -294,7 +294,7 @@ impl AssetProcessor {
         let reader = source.reader();
         match reader.read_meta_bytes(path.path()).await {
             Ok(_) => return Err(WriteDefaultMetaError::MetaAlreadyExists),
-            Err(AssetReaderError::NotFound(_)) => {
+            Err(AssetReaderError::NotFound(_) | AssetReaderError::NotFoundInSearchPaths { .. }) => {
                 // The meta file couldn't be found so just fall through.
             }
             Err(AssetReaderError::Io(err)) => {
@@ -303,6 +303,18 @@ impl AssetProcessor {
             Err(AssetReaderError::HttpError(err)) => {
                 return Err(WriteDefaultMetaError::HttpErrorFromExistingMetaCheck(err))
             }
+            Err(AssetReaderError::DanglingSymlink { link, .. }) => {
+                return Err(WriteDefaultMetaError::DanglingSymlinkFromExistingMetaCheck(
+                    link,
+                ))
+            }
+            Err(AssetReaderError::Shutdown) => {
+                return Err(WriteDefaultMetaError::ShutdownDuringExistingMetaCheck)
+            }
+            Err(AssetReaderError::TruncatedDuringRead { .. }) => {
+                // a meta file was there to be read, so it must not be overwritten
+                return Err(WriteDefaultMetaError::MetaAlreadyExists);
+            }
         }
 
         let writer = source.writer()?;
@@ -388,10 +400,14 @@ impl AssetProcessor {
                     }
                     Err(err) => {
                         match err {
-                            AssetReaderError::NotFound(_) => {
+                            AssetReaderError::NotFound(_)
+                            | AssetReaderError::NotFoundInSearchPaths { .. } => {
                                 // if the path is not found, a processed version does not exist
                             }
-                            AssetReaderError::Io(err) => {
+                            err @ (AssetReaderError::Io(_)
+                            | AssetReaderError::DanglingSymlink { .. }
+                            | AssetReaderError::Shutdown
+                            | AssetReaderError::TruncatedDuringRead { .. }) => {
                                 error!(
                                     "Path '{}' was removed, but the destination reader could not determine if it \
                                     was a folder or a file due to the following error: {err}",
@@ -456,7 +472,8 @@ impl AssetProcessor {
                 }
             }
             Err(err) => match err {
-                AssetReaderError::NotFound(_err) => {
+                AssetReaderError::NotFound(_err)
+                | AssetReaderError::NotFoundInSearchPaths { .. } => {
                     // The processed folder does not exist. No need to update anything
                 }
                 AssetReaderError::HttpError(status) => {
@@ -466,7 +483,10 @@ impl AssetProcessor {
                         in the source directory. Restart the asset processor to fully reprocess assets. HTTP Status Code {status}"
                     );
                 }
-                AssetReaderError::Io(err) => {
+                err @ (AssetReaderError::Io(_)
+                | AssetReaderError::DanglingSymlink { .. }
+                | AssetReaderError::Shutdown
+                | AssetReaderError::TruncatedDuringRead { .. }) => {
                     self.log_unrecoverable().await;
                     error!(
                         "Unrecoverable Error: Failed to read the processed assets at {path:?} in order to remove assets that no longer exist \
@@ -486,6 +506,10 @@ impl AssetProcessor {
                         error!("Failed to remove destination folder that no longer exists in {asset_path}: {err}");
                     }
                 }
+                err => {
+                    let asset_path = AssetPath::from_path(path).with_source(source.id());
+                    error!("Failed to remove destination folder that no longer exists in {asset_path}: {err}");
+                }
             }
         }
     }
@@ -933,7 +957,7 @@ impl AssetProcessor {
             };
 
             writer
-                .flush()
+                .close()
                 .await
                 .map_err(|e| ProcessError::AssetWriterError {
                     path: asset_path.clone(),
@@ -1015,6 +1039,7 @@ impl AssetProcessor {
                                                 unrecoverable_err(&err);
                                             }
                                         }
+                                        err => unrecoverable_err(&err),
                                     }
                                 }
                                 if let Err(err) = processed_writer.remove_meta(path.path()).await {
@@ -1025,6 +1050,7 @@ impl AssetProcessor {
                                                 unrecoverable_err(&err);
                                             }
                                         }
+                                        err => unrecoverable_err(&err),
                                     }
                                 }
                             }
@@ -1331,7 +1357,7 @@ impl ProcessorAssetInfos {
                 trace!("No loader found for {asset_path}");
             }
             Err(ProcessError::AssetReaderError {
-                err: AssetReaderError::NotFound(_),
+                err: AssetReaderError::NotFound(_) | AssetReaderError::NotFoundInSearchPaths { .. },
                 ..
             }) => {
                 // if there is no asset source, no processing can be done
@@ -0,0 +1,527 @@
+use crate::AssetPath;
+use alloc::{boxed::Box, collections::VecDeque, format, string::String, sync::Arc, vec::Vec};
+#[cfg(feature = "descriptor_metrics")]
+use bevy_platform_support::time::Instant;
+#[cfg(feature = "descriptor_metrics")]
+use core::time::Duration;
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+use parking_lot::{Mutex, RwLock};
+
+/// Returns the number of assets the [`AssetServer`](super::AssetServer) loads at once by default,
+/// which keeps the files it opens within the limit of the platform.
+pub(crate) fn default_descriptor_limit() -> usize {
+    //Normal limits are cut in half to allow for .meta files and sub 1 for headroom
+    if cfg!(target_os = "ios") {
+        /*
+        https://forum.vizrt.com/index.php?threads/ios-too-many-open-files-with-little-number-of-sources-receivers.250906/#:~:text=The%20number%20of%20sockets%20quickly,iOS%20and%20crashes%20the%20application.
+        Documentation is fairly scarce on the actual limit, there is no documentation that I've been able to find from apple
+        */
+        127 // The normal limit is 256, cut in half for .meta files and sub 1 because 128 still throws the occasional error (3 failed files out of 1500)
+    } else if cfg!(target_os = "macos") {
+        /*
+        https://krypted.com/mac-os-x/maximum-files-in-mac-os-x/
+        Running `ulimit -n` on a MBP M3-Max yields 2560. In empirical testing when using the exact limit
+        some failures would still squeak through. This also leaves a small amount of headroom for direct
+        std::fs calls by the client application
+        */
+        1279
+    } else {
+        /*
+        https://docs.pingidentity.com/pingdirectory/latest/installing_the_pingdirectory_suite_of_products/pd_ds_config_file_descriptor_limits.html#:~:text=Many%20Linux%20distributions%20have%20a,large%20number%20of%20concurrent%20connections.
+        Setting this as a 'sensible' default in lieu of a cross platform way to determine file descriptor limits. For OSX/Linux we could potentially run ulimit at runtime, but client applications could also chunk their calls to asset_server
+        as a workaround. Apps that exceed this limit would be fairly exceptional.
+        */
+        511
+    }
+}
+
+/// How urgently an asset is needed, which decides the order in which loads waiting for the
+/// [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit) are started.
+///
+/// Loads of higher priority start before any waiting load of lower priority, and loads of the
+/// same priority start in the order they were requested.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LoadPriority {
+    /// Assets that can arrive late, such as textures streamed in the background.
+    Low,
+    /// The priority of loads started by [`AssetServer::load`](super::AssetServer::load).
+    #[default]
+    Normal,
+    /// Assets needed right away, such as the collision mesh of the next level.
+    High,
+}
+
+/// Why the [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit) changed, as
+/// reported to the observer set with
+/// [`AssetServer::set_limit_change_observer`](super::AssetServer::set_limit_change_observer).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LimitChangeReason {
+    /// The limit was set with [`AssetServer::set_descriptor_limit`](super::AssetServer::set_descriptor_limit).
+    Manual,
+    /// The limit was sized with
+    /// [`AssetServer::size_descriptor_limit_for`](super::AssetServer::size_descriptor_limit_for).
+    SizedForConcurrency,
+}
+
+/// A function called with the new limit and the reason whenever the
+/// [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit) changes.
+pub type LimitChangeObserver = Box<dyn Fn(usize, LimitChangeReason) + Send + Sync>;
+
+/// Assets whose loads do not wait for the
+/// [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit), set with
+/// [`AssetServer::set_uncounted_assets`](super::AssetServer::set_uncounted_assets).
+///
+/// This is meant for a small set of tiny files that are read often, such as a `version.txt`,
+/// which should never queue behind a large batch of loads.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UncountedAssets {
+    /// Extensions of uncounted assets, without the leading dot. An asset matches if its file
+    /// name ends with one of them, so `ron` matches both `a.ron` and `a.scene.ron`.
+    pub extensions: Vec<String>,
+    /// Paths of uncounted assets. Labels are ignored, so `a.gltf` also covers `a.gltf#Mesh0`.
+    pub paths: Vec<AssetPath<'static>>,
+}
+
+impl UncountedAssets {
+    /// Returns true if loads of `path` do not wait for the descriptor limit.
+    pub(crate) fn contains(&self, path: &AssetPath) -> bool {
+        if !self.paths.is_empty() {
+            let path = path.without_label();
+            if self.paths.iter().any(|uncounted| *uncounted == path) {
+                return true;
+            }
+        }
+        let Some(extension) = path.get_full_extension() else {
+            return false;
+        };
+        self.extensions.iter().any(|uncounted| {
+            extension == *uncounted || extension.ends_with(&format!(".{uncounted}"))
+        })
+    }
+}
+
+/// A handle to the limit behind an [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit),
+/// returned by [`AssetServer::shared_descriptor_limit`](super::AssetServer::shared_descriptor_limit).
+///
+/// Cloning the handle does not create a new limit: every clone, and every server that
+/// [adopts](super::AssetServer::adopt_descriptor_limit) it, draws from the same permits.
+#[derive(Clone)]
+pub struct SharedDescriptorLimit {
+    pub(crate) limit: Arc<DescriptorLimit>,
+}
+
+impl SharedDescriptorLimit {
+    /// Creates a limit of `limit` permits, which is raised to one if it is zero, that is not yet
+    /// used by any server.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: Arc::new(DescriptorLimit::new(limit)),
+        }
+    }
+
+    /// Returns the number of permits.
+    pub fn limit(&self) -> usize {
+        self.limit.limit()
+    }
+
+    /// Returns the number of permits that can be taken right now without waiting.
+    pub fn available(&self) -> usize {
+        self.limit.available()
+    }
+}
+
+/// Whether directories read by [`AssetServer::load_folder`](super::AssetServer::load_folder)
+/// hold a permit while they are open, set with
+/// [`AssetServer::set_directory_handle_limit`](super::AssetServer::set_directory_handle_limit).
+///
+/// On some platforms a directory handle does not count against the same limit as files, so
+/// counting it only slows folder loads down. Where it does, counting it keeps a large folder load
+/// within the limit.
+#[derive(Clone, Default)]
+pub enum DirectoryHandleLimit {
+    /// Directories are read without a permit.
+    #[default]
+    Uncounted,
+    /// Directories hold a permit of the
+    /// [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit), like loads.
+    Loads,
+    /// Directories hold a permit of a limit of their own.
+    Separate(SharedDescriptorLimit),
+}
+
+/// Limits how many assets are loaded at once, so that the asset server does not open more files
+/// than the OS allows.
+///
+/// This is a semaphore whose permit count can be changed while loads are running. Lowering it
+/// does not interrupt loads holding a permit, it takes effect as they complete.
+pub(crate) struct DescriptorLimit {
+    state: Mutex<LimitState>,
+    observer: RwLock<Option<Arc<dyn Fn(usize, LimitChangeReason) + Send + Sync>>>,
+}
+
+struct LimitState {
+    limit: usize,
+    in_use: usize,
+    waiters: VecDeque<Arc<Waiter>>,
+    #[cfg(feature = "descriptor_metrics")]
+    max_wait: Duration,
+}
+
+/// A load waiting for a permit. Its fields are only accessed with the state locked.
+struct Waiter {
+    priority: LoadPriority,
+    granted: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl LimitState {
+    /// Queues `waiter` behind every waiting load of the same or a higher priority.
+    fn enqueue(&mut self, waiter: Arc<Waiter>) {
+        let index = self
+            .waiters
+            .iter()
+            .position(|other| other.priority < waiter.priority)
+            .unwrap_or(self.waiters.len());
+        self.waiters.insert(index, waiter);
+    }
+
+    /// Hands permits to waiting loads, in order, as long as the limit allows.
+    fn grant(&mut self) {
+        while self.in_use < self.limit {
+            let Some(waiter) = self.waiters.pop_front() else {
+                break;
+            };
+            self.in_use += 1;
+            waiter.granted.store(true, Ordering::Relaxed);
+            if let Some(waker) = waiter.waker.lock().take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl DescriptorLimit {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            state: Mutex::new(LimitState {
+                limit: limit.max(1),
+                in_use: 0,
+                waiters: VecDeque::new(),
+                #[cfg(feature = "descriptor_metrics")]
+                max_wait: Duration::ZERO,
+            }),
+            observer: RwLock::new(None),
+        }
+    }
+
+    /// Returns the number of permits.
+    pub(crate) fn limit(&self) -> usize {
+        self.state.lock().limit
+    }
+
+    /// Returns the number of permits that can be acquired right now without waiting.
+    pub(crate) fn available(&self) -> usize {
+        let state = self.state.lock();
+        state.limit.saturating_sub(state.in_use)
+    }
+
+    /// Estimates how many waves it takes to acquire `concurrent` permits at once: the first wave
+    /// takes the permits available now, and every further wave waits for a full limit's worth of
+    /// loads to complete.
+    pub(crate) fn wait_batches(&self, concurrent: usize) -> usize {
+        let state = self.state.lock();
+        let available = state.limit.saturating_sub(state.in_use);
+        if concurrent == 0 {
+            0
+        } else if concurrent <= available {
+            1
+        } else {
+            1 + (concurrent - available).div_ceil(state.limit)
+        }
+    }
+
+    /// Changes the number of permits to `limit`, which is raised to one if it is zero, and returns
+    /// the new number. If the number changed, the observer is notified with `reason`.
+    pub(crate) fn set_limit(&self, limit: usize, reason: LimitChangeReason) -> usize {
+        let (previous, limit) = {
+            let mut state = self.state.lock();
+            let previous = state.limit;
+            state.limit = limit.max(1);
+            state.grant();
+            (previous, state.limit)
+        };
+        // called without any lock held, so the observer may query or change the limit itself
+        let observer = self.observer.read().clone();
+        if let Some(observer) = observer.filter(|_| previous != limit) {
+            observer(limit, reason);
+        }
+        limit
+    }
+
+    pub(crate) fn set_observer(&self, observer: Option<LimitChangeObserver>) {
+        *self.observer.write() = observer.map(Arc::from);
+    }
+
+    /// Returns the longest time a call to [`DescriptorLimit::acquire`] waited for a permit since
+    /// the limit was created or [`DescriptorLimit::reset_max_wait`] was called.
+    #[cfg(feature = "descriptor_metrics")]
+    pub(crate) fn max_wait(&self) -> Duration {
+        self.state.lock().max_wait
+    }
+
+    #[cfg(feature = "descriptor_metrics")]
+    pub(crate) fn reset_max_wait(&self) {
+        self.state.lock().max_wait = Duration::ZERO;
+    }
+
+    /// Takes a permit if one is available and no load is waiting for one, returning whether it
+    /// was taken. A permit taken this way must be released with [`DescriptorLimit::release`].
+    fn try_take(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.waiters.is_empty() && state.in_use < state.limit {
+            state.in_use += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Releases a permit, handing it to the next waiting load.
+    fn release(&self) {
+        let mut state = self.state.lock();
+        state.in_use -= 1;
+        state.grant();
+    }
+
+    /// Takes a permit without waiting, returning `None` if none is available or loads are already
+    /// waiting for one. The permit is released when the returned reservation is dropped.
+    pub(crate) fn try_reserve(self: &Arc<Self>) -> Option<DescriptorReservation> {
+        self.try_take().then(|| DescriptorReservation {
+            limit: self.clone(),
+        })
+    }
+
+    /// Waits for a permit, which is released when the returned guard is dropped. Permits are
+    /// handed out by priority, then in the order they were requested.
+    pub(crate) fn acquire(&self, priority: LoadPriority) -> Acquire<'_> {
+        Acquire {
+            limit: self,
+            priority,
+            waiter: None,
+            #[cfg(feature = "descriptor_metrics")]
+            queued_at: None,
+        }
+    }
+}
+
+/// The future returned by [`DescriptorLimit::acquire`].
+pub(crate) struct Acquire<'a> {
+    limit: &'a DescriptorLimit,
+    priority: LoadPriority,
+    waiter: Option<Arc<Waiter>>,
+    #[cfg(feature = "descriptor_metrics")]
+    queued_at: Option<Instant>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = DescriptorPermit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let limit = self.limit;
+        let mut state = limit.state.lock();
+        match &self.waiter {
+            Some(waiter) if waiter.granted.load(Ordering::Relaxed) => {
+                self.waiter = None;
+                #[cfg(feature = "descriptor_metrics")]
+                if let Some(queued_at) = self.queued_at {
+                    state.max_wait = state.max_wait.max(queued_at.elapsed());
+                }
+                Poll::Ready(DescriptorPermit { limit })
+            }
+            Some(waiter) => {
+                *waiter.waker.lock() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            None if state.waiters.is_empty() && state.in_use < state.limit => {
+                state.in_use += 1;
+                Poll::Ready(DescriptorPermit { limit })
+            }
+            None => {
+                let waiter = Arc::new(Waiter {
+                    priority: self.priority,
+                    granted: AtomicBool::new(false),
+                    waker: Mutex::new(Some(cx.waker().clone())),
+                });
+                state.enqueue(waiter.clone());
+                self.waiter = Some(waiter);
+                #[cfg(feature = "descriptor_metrics")]
+                {
+                    self.queued_at = Some(Instant::now());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+        let mut state = self.limit.state.lock();
+        if waiter.granted.load(Ordering::Relaxed) {
+            // the load was cancelled after it was handed a permit, which goes to the next one
+            state.in_use -= 1;
+            state.grant();
+        } else {
+            state.waiters.retain(|other| !Arc::ptr_eq(other, &waiter));
+        }
+    }
+}
+
+/// A permit to load an asset, released when dropped.
+pub(crate) struct DescriptorPermit<'a> {
+    limit: &'a DescriptorLimit,
+}
+
+impl Drop for DescriptorPermit<'_> {
+    fn drop(&mut self) {
+        self.limit.release();
+    }
+}
+
+/// A permit of the [`AssetServer::descriptor_limit`](super::AssetServer::descriptor_limit) taken
+/// with [`AssetServer::try_reserve_descriptor`](super::AssetServer::try_reserve_descriptor).
+/// Loads cannot use it until it is dropped.
+#[must_use = "the permit is released as soon as the reservation is dropped"]
+pub struct DescriptorReservation {
+    limit: Arc<DescriptorLimit>,
+}
+
+impl Drop for DescriptorReservation {
+    fn drop(&mut self) {
+        self.limit.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use futures_lite::{future::poll_once, FutureExt};
+
+    #[test]
+    fn lowering_the_limit_waits_for_permits_in_use() {
+        let limit = DescriptorLimit::new(2);
+        let first = bevy_tasks::block_on(limit.acquire(LoadPriority::Normal));
+        let second = bevy_tasks::block_on(limit.acquire(LoadPriority::Normal));
+        limit.set_limit(1, LimitChangeReason::Manual);
+        let mut third = limit.acquire(LoadPriority::Normal).boxed_local();
+        drop(first);
+        assert!(bevy_tasks::block_on(poll_once(&mut third)).is_none());
+        drop(second);
+        assert!(bevy_tasks::block_on(poll_once(&mut third)).is_some());
+    }
+
+    #[test]
+    fn raising_the_limit_wakes_waiting_loads() {
+        let limit = DescriptorLimit::new(1);
+        let _first = bevy_tasks::block_on(limit.acquire(LoadPriority::Normal));
+        let mut second = limit.acquire(LoadPriority::Normal).boxed_local();
+        assert!(bevy_tasks::block_on(poll_once(&mut second)).is_none());
+        assert_eq!(limit.set_limit(2, LimitChangeReason::Manual), 2);
+        assert!(bevy_tasks::block_on(poll_once(&mut second)).is_some());
+    }
+
+    #[test]
+    fn higher_priority_loads_start_first() {
+        let limit = DescriptorLimit::new(1);
+        let first = bevy_tasks::block_on(limit.acquire(LoadPriority::Normal));
+        let mut low = limit.acquire(LoadPriority::Low).boxed_local();
+        let mut normal = limit.acquire(LoadPriority::Normal).boxed_local();
+        let mut high = limit.acquire(LoadPriority::High).boxed_local();
+        for waiting in [&mut low, &mut normal, &mut high] {
+            assert!(bevy_tasks::block_on(poll_once(waiting)).is_none());
+        }
+        drop(first);
+        assert!(bevy_tasks::block_on(poll_once(&mut low)).is_none());
+        assert!(bevy_tasks::block_on(poll_once(&mut normal)).is_none());
+        let high = bevy_tasks::block_on(poll_once(&mut high)).unwrap();
+        drop(high);
+        assert!(bevy_tasks::block_on(poll_once(&mut low)).is_none());
+        assert!(bevy_tasks::block_on(poll_once(&mut normal)).is_some());
+        assert!(bevy_tasks::block_on(poll_once(&mut low)).is_some());
+    }
+
+    #[test]
+    fn try_reserve_only_takes_a_free_permit() {
+        let limit = Arc::new(DescriptorLimit::new(2));
+        let first = limit.try_reserve();
+        assert!(first.is_some());
+        let second = limit.try_reserve();
+        assert!(second.is_some());
+        assert!(limit.try_reserve().is_none());
+        let mut waiting = limit.acquire(LoadPriority::Normal).boxed_local();
+        assert!(bevy_tasks::block_on(poll_once(&mut waiting)).is_none());
+        drop(first);
+        // the freed permit goes to the waiting load, not to a new reservation
+        assert!(limit.try_reserve().is_none());
+        let permit = bevy_tasks::block_on(poll_once(&mut waiting)).unwrap();
+        drop((permit, second));
+        assert_eq!(limit.available(), 2);
+        assert!(limit.try_reserve().is_some());
+    }
+
+    #[test]
+    fn uncounted_assets_match_extensions_and_paths() {
+        let uncounted = UncountedAssets {
+            extensions: vec!["ron".into()],
+            paths: vec![AssetPath::from("version.txt")],
+        };
+        assert!(uncounted.contains(&AssetPath::from("a.ron")));
+        assert!(uncounted.contains(&AssetPath::from("dir/a.scene.ron")));
+        assert!(uncounted.contains(&AssetPath::from("version.txt#Label")));
+        assert!(!uncounted.contains(&AssetPath::from("a.ronx")));
+        assert!(!uncounted.contains(&AssetPath::from("other/version.txt")));
+        assert!(!uncounted.contains(&AssetPath::from("source://version.txt")));
+    }
+
+    #[test]
+    fn wait_batches_count_the_permits_in_use() {
+        let limit = DescriptorLimit::new(4);
+        assert_eq!(limit.wait_batches(0), 0);
+        assert_eq!(limit.wait_batches(4), 1);
+        assert_eq!(limit.wait_batches(5), 2);
+        let _first = bevy_tasks::block_on(limit.acquire(LoadPriority::Normal));
+        assert_eq!(limit.available(), 3);
+        assert_eq!(limit.wait_batches(3), 1);
+        assert_eq!(limit.wait_batches(4), 2);
+        assert_eq!(limit.wait_batches(11), 3);
+    }
+
+    #[test]
+    fn observer_sees_each_change_of_the_limit() {
+        let limit = DescriptorLimit::new(4);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let seen = changes.clone();
+        limit.set_observer(Some(Box::new(move |limit, reason| {
+            seen.lock().push((limit, reason));
+        })));
+        limit.set_limit(8, LimitChangeReason::SizedForConcurrency);
+        limit.set_limit(8, LimitChangeReason::Manual);
+        limit.set_limit(0, LimitChangeReason::Manual);
+        assert_eq!(
+            *changes.lock(),
+            [
+                (8, LimitChangeReason::SizedForConcurrency),
+                (1, LimitChangeReason::Manual)
+            ]
+        );
+    }
+}
@@ -1,12 +1,18 @@
+mod descriptor_limit;
 mod info;
 mod loaders;
 
+pub use descriptor_limit::{
+    DescriptorReservation, DirectoryHandleLimit, LimitChangeObserver, LimitChangeReason,
+    LoadPriority, SharedDescriptorLimit, UncountedAssets,
+};
+
 use crate::{
     folder::LoadedFolder,
     io::{
         AssetReaderError, AssetSource, AssetSourceEvent, AssetSourceId, AssetSources,
         AssetWriterError, ErasedAssetReader, MissingAssetSourceError, MissingAssetWriterError,
-        MissingProcessedAssetReaderError, Reader,
+        MissingProcessedAssetReaderError, PathStream, Reader,
     },
     loader::{AssetLoader, ErasedAssetLoader, LoadContext, LoadedAsset},
     meta::{
@@ -24,13 +30,13 @@ use alloc::{
     string::{String, ToString},
     sync::Arc,
 };
-use async_lock::Semaphore;
 use atomicow::CowArc;
 use bevy_ecs::prelude::*;
-use bevy_platform_support::collections::HashSet;
+use bevy_platform_support::collections::{HashMap, HashSet};
 use bevy_tasks::IoTaskPool;
 use core::{any::TypeId, future::Future, panic::AssertUnwindSafe, task::Poll};
 use crossbeam_channel::{Receiver, Sender};
+use descriptor_limit::*;
 use either::Either;
 use futures_lite::{FutureExt, StreamExt};
 use info::*;
@@ -38,7 +44,7 @@ use loaders::*;
 use parking_lot::{RwLock, RwLockWriteGuard};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Loads and tracks the state of [`Asset`] values from a configured [`AssetReader`](crate::io::AssetReader).
 /// This can be used to kick off new asset loads and retrieve their current load states.
@@ -71,7 +77,40 @@ pub(crate) struct AssetServerData {
     meta_check: AssetMetaCheck,
 
     ///Used to ensure the `asset_server` does not try to acquire more loaders (and thus `file_handles`) than the OS allows
-    asset_counter: Semaphore,
+    descriptor_limit: RwLock<Arc<DescriptorLimit>>,
+    /// Assets loaded without a permit of the `descriptor_limit`
+    uncounted: RwLock<UncountedAssets>,
+    directory_handle_limit: RwLock<DirectoryHandleLimit>,
+    /// Limits used instead of the `descriptor_limit` by loads from specific sources
+    source_descriptor_limits: RwLock<HashMap<AssetSourceId<'static>, Arc<DescriptorLimit>>>,
+}
+
+impl AssetServerData {
+    /// Returns the limit loads currently draw their permits from, which changes when another
+    /// limit is adopted.
+    fn current_descriptor_limit(&self) -> Arc<DescriptorLimit> {
+        self.descriptor_limit.read().clone()
+    }
+
+    /// Returns the limit loads from `source` draw their permits from.
+    fn descriptor_limit_for(&self, source: &AssetSourceId) -> Arc<DescriptorLimit> {
+        let source_limits = self.source_descriptor_limits.read();
+        if !source_limits.is_empty() {
+            if let Some(limit) = source_limits.get(&source.clone_owned()) {
+                return limit.clone();
+            }
+        }
+        self.current_descriptor_limit()
+    }
+
+    /// Returns the limit directories read by folder loads draw their permits from, if any.
+    fn directory_descriptor_limit(&self) -> Option<Arc<DescriptorLimit>> {
+        match &*self.directory_handle_limit.read() {
+            DirectoryHandleLimit::Uncounted => None,
+            DirectoryHandleLimit::Loads => Some(self.current_descriptor_limit()),
+            DirectoryHandleLimit::Separate(shared) => Some(shared.limit.clone()),
+        }
+    }
 }
 
 /// The "asset mode" the server is currently in.
@@ -124,31 +163,6 @@ impl AssetServer {
         let mut infos = AssetInfos::default();
         infos.watching_for_changes = watching_for_changes;
 
-        //Normal limits are cut in half to allow for .meta files and sub 1 for headroom
-        #[cfg(target_os = "ios")]
-        /*
-        https://forum.vizrt.com/index.php?threads/ios-too-many-open-files-with-little-number-of-sources-receivers.250906/#:~:text=The%20number%20of%20sockets%20quickly,iOS%20and%20crashes%20the%20application.
-        Documentation is fairly scarce on the actual limit, there is no documentation that I've been able to find from apple
-        */
-        let file_limit = 127; // The normal limit is 256, cut in half for .meta files and sub 1 because 128 still throws the occasional error (3 failed files out of 1500)
-
-        /*
-        https://krypted.com/mac-os-x/maximum-files-in-mac-os-x/
-        Running `ulimit -n` on a MBP M3-Max yields 2560. In empirical testing when using the exact limit
-        some failures would still squeak through. This also leaves a small amount of headroom for direct
-        std::fs calls by the client application
-        */
-        #[cfg(target_os = "macos")]
-        let file_limit = 1279;
-
-        /*
-        https://docs.pingidentity.com/pingdirectory/latest/installing_the_pingdirectory_suite_of_products/pd_ds_config_file_descriptor_limits.html#:~:text=Many%20Linux%20distributions%20have%20a,large%20number%20of%20concurrent%20connections.
-        Setting this as a 'sensible' default in lieu of a cross platform way to determine file descriptor limits. For OSX/Linux we could potentially run ulimit at runtime, but client applications could also chunk their calls to asset_server
-        as a workaround. Apps that exceed this limit would be fairly exceptional.
-        */
-        #[cfg(all(not(target_os = "macos"), not(target_os = "ios")))]
-        let file_limit = 511;
-
         Self {
             data: Arc::new(AssetServerData {
                 sources,
@@ -158,7 +172,12 @@ impl AssetServer {
                 asset_event_receiver,
                 loaders,
                 infos: RwLock::new(infos),
-                asset_counter: Semaphore::new(file_limit),
+                descriptor_limit: RwLock::new(Arc::new(DescriptorLimit::new(
+                    default_descriptor_limit(),
+                ))),
+                uncounted: RwLock::new(UncountedAssets::default()),
+                directory_handle_limit: RwLock::new(DirectoryHandleLimit::default()),
+                source_descriptor_limits: RwLock::new(HashMap::default()),
             }),
         }
     }
@@ -171,6 +190,168 @@ impl AssetServer {
         self.data.sources.get(source.into())
     }
 
+    /// Returns how many assets this server loads at once. Further loads wait for one of the running
+    /// loads to complete, which keeps the files opened by the server within the limit of the
+    /// platform.
+    ///
+    /// Each load holds a single permit while it reads both the asset and its `.meta` file, so an
+    /// asset never takes two permits. The default limit is half the platform's file limit to
+    /// leave room for both files being open at once.
+    pub fn descriptor_limit(&self) -> usize {
+        self.data.current_descriptor_limit().limit()
+    }
+
+    /// Changes the limit returned by [`AssetServer::descriptor_limit`] to `new_limit`, which is
+    /// raised to one if it is zero. Returns the new limit.
+    ///
+    /// Raising the limit immediately starts loads that were waiting. Lowering it does not
+    /// interrupt running loads, it takes effect as they complete. This lets the limit be tuned
+    /// at runtime, for example from a setting that caps how aggressively assets are loaded.
+    pub fn set_descriptor_limit(&self, new_limit: usize) -> usize {
+        self.data
+            .current_descriptor_limit()
+            .set_limit(new_limit, LimitChangeReason::Manual)
+    }
+
+    /// Sizes the limit returned by [`AssetServer::descriptor_limit`] to comfortably fit
+    /// `expected_concurrent` loads running at once, adding a quarter of headroom (at least one).
+    /// The limit is never raised above the default for the platform, and a warning is logged if
+    /// the request had to be clamped. Returns the new limit.
+    ///
+    /// This right-sizes the server for a known workload, such as a startup that loads a fixed
+    /// set of assets. Loads already running are not interrupted if the limit is lowered.
+    pub fn size_descriptor_limit_for(&self, expected_concurrent: usize) -> usize {
+        let requested = expected_concurrent.saturating_add(expected_concurrent.div_ceil(4).max(1));
+        let os_limit = default_descriptor_limit();
+        if requested > os_limit {
+            warn!(
+                "{expected_concurrent} concurrent asset loads need a limit of {requested}, which exceeds the platform limit of {os_limit}. Clamping to {os_limit}."
+            );
+        }
+        self.data.current_descriptor_limit().set_limit(
+            requested.min(os_limit),
+            LimitChangeReason::SizedForConcurrency,
+        )
+    }
+
+    /// Returns true if `concurrent` loads started now would all run at once, without waiting for
+    /// running loads limited by [`AssetServer::descriptor_limit`] to complete.
+    ///
+    /// Loads that start or complete in the meantime change the answer, so this is a hint for
+    /// deciding whether to split up a large batch of loads, not a guarantee.
+    pub fn would_fit(&self, concurrent: usize) -> bool {
+        concurrent <= self.data.current_descriptor_limit().available()
+    }
+
+    /// Estimates how many sequential waves `concurrent` loads started now would run in, given the
+    /// [`AssetServer::descriptor_limit`] and the loads already running. Returns 1 if they
+    /// [fit](AssetServer::would_fit) and 0 if `concurrent` is zero.
+    pub fn estimated_wait_batches(&self, concurrent: usize) -> usize {
+        self.data
+            .current_descriptor_limit()
+            .wait_batches(concurrent)
+    }
+
+    /// Takes one permit of the [`AssetServer::descriptor_limit`] without waiting, returning `None`
+    /// if all permits are in use or loads are already waiting for one. Loads cannot use the
+    /// permit until the returned [`DescriptorReservation`] is dropped.
+    ///
+    /// This lets work outside the server, such as a large export, check for a free slot and hold
+    /// it before opening files of its own, instead of competing with loads for descriptors.
+    pub fn try_reserve_descriptor(&self) -> Option<DescriptorReservation> {
+        self.data.current_descriptor_limit().try_reserve()
+    }
+
+    /// Returns a handle to the limit behind [`AssetServer::descriptor_limit`], which other
+    /// servers can [adopt](AssetServer::adopt_descriptor_limit) to share a single budget of open
+    /// files.
+    pub fn shared_descriptor_limit(&self) -> SharedDescriptorLimit {
+        SharedDescriptorLimit {
+            limit: self.data.current_descriptor_limit(),
+        }
+    }
+
+    /// Makes this server draw its permits from `shared` instead of its own limit, so that loads of
+    /// every server sharing it count against one [`AssetServer::descriptor_limit`]. Changes of
+    /// the limit and its observer made through any of the servers apply to all of them.
+    ///
+    /// Loads that already hold or wait for a permit of the previous limit keep using it.
+    pub fn adopt_descriptor_limit(&self, shared: &SharedDescriptorLimit) {
+        *self.data.descriptor_limit.write() = shared.limit.clone();
+    }
+
+    /// Sets which assets are loaded without waiting for the [`AssetServer::descriptor_limit`],
+    /// replacing the assets set before. Their loads neither take a permit nor queue behind loads
+    /// waiting for one.
+    ///
+    /// Keep the set small: every uncounted load in flight opens files beyond the limit.
+    pub fn set_uncounted_assets(&self, uncounted: UncountedAssets) {
+        *self.data.uncounted.write() = uncounted;
+    }
+
+    /// Makes loads from `source` draw their permits from `limit` instead of the
+    /// [`AssetServer::descriptor_limit`], or from it again if `limit` is `None`.
+    ///
+    /// This gives a source that is not backed by local files, such as one fetching assets over
+    /// HTTP, a budget of its own, like a connection limit, so that its loads neither wait for
+    /// local loads nor hold up local loads while they wait on the network.
+    pub fn set_source_descriptor_limit<'a>(
+        &self,
+        source: impl Into<AssetSourceId<'a>>,
+        limit: Option<&SharedDescriptorLimit>,
+    ) {
+        let source = source.into().clone_owned();
+        let mut source_limits = self.data.source_descriptor_limits.write();
+        match limit {
+            Some(shared) => source_limits.insert(source, shared.limit.clone()),
+            None => source_limits.remove(&source),
+        };
+    }
+
+    /// Sets whether directories read by [`AssetServer::load_folder`] hold a permit while they are
+    /// open. By default they do not.
+    ///
+    /// A counted directory is read to the end while the permit is held, and the permit is
+    /// released before its assets are loaded, so a folder load never holds more than one.
+    pub fn set_directory_handle_limit(&self, limit: DirectoryHandleLimit) {
+        *self.data.directory_handle_limit.write() = limit;
+    }
+
+    /// Sets a function that is called with the new limit and the reason whenever the
+    /// [`AssetServer::descriptor_limit`] changes, replacing any function set before. Setting the
+    /// limit to the value it already has does not call it.
+    ///
+    /// This gives diagnostics a single place to follow every change of the limit, whichever
+    /// method made it.
+    pub fn set_limit_change_observer(&self, observer: LimitChangeObserver) {
+        self.data
+            .current_descriptor_limit()
+            .set_observer(Some(observer));
+    }
+
+    /// Removes the function set with [`AssetServer::set_limit_change_observer`].
+    pub fn clear_limit_change_observer(&self) {
+        self.data.current_descriptor_limit().set_observer(None);
+    }
+
+    /// Returns the longest time a load has waited for one of the loads limited by
+    /// [`AssetServer::descriptor_limit`] to complete, since the server was created or
+    /// [`AssetServer::reset_max_acquire_wait`] was called.
+    ///
+    /// If this stays near zero, the limit is not what slows loading down. If it climbs, raising
+    /// the limit or loading fewer assets at once will help.
+    #[cfg(feature = "descriptor_metrics")]
+    pub fn max_acquire_wait(&self) -> core::time::Duration {
+        self.data.current_descriptor_limit().max_wait()
+    }
+
+    /// Resets the wait returned by [`AssetServer::max_acquire_wait`] to zero, for example to
+    /// measure a single loading screen.
+    #[cfg(feature = "descriptor_metrics")]
+    pub fn reset_max_acquire_wait(&self) {
+        self.data.current_descriptor_limit().reset_max_wait();
+    }
+
     /// Returns true if the [`AssetServer`] watches for changes.
     pub fn watching_for_changes(&self) -> bool {
         self.data.infos.read().watching_for_changes
@@ -342,7 +523,7 @@ impl AssetServer {
     /// The asset load will fail and an error will be printed to the logs if the asset stored at `path` is not of type `A`.
     #[must_use = "not using the returned strong handle may result in the unexpected release of the asset"]
     pub fn load<'a, A: Asset>(&self, path: impl Into<AssetPath<'a>>) -> Handle<A> {
-        self.load_with_meta_transform(path, None, ())
+        self.load_with_meta_transform(path, None, (), LoadPriority::Normal)
     }
 
     /// Begins loading an [`Asset`] of type `A` stored at `path` while holding a guard item.
@@ -366,7 +547,7 @@ impl AssetServer {
         path: impl Into<AssetPath<'a>>,
         guard: G,
     ) -> Handle<A> {
-        self.load_with_meta_transform(path, None, guard)
+        self.load_with_meta_transform(path, None, guard, LoadPriority::Normal)
     }
 
     /// Begins loading an [`Asset`] of type `A` stored at `path`. The given `settings` function will override the asset's
@@ -378,7 +559,12 @@ impl AssetServer {
         path: impl Into<AssetPath<'a>>,
         settings: impl Fn(&mut S) + Send + Sync + 'static,
     ) -> Handle<A> {
-        self.load_with_meta_transform(path, Some(loader_settings_meta_transform(settings)), ())
+        self.load_with_meta_transform(
+            path,
+            Some(loader_settings_meta_transform(settings)),
+            (),
+            LoadPriority::Normal,
+        )
     }
 
     /// Begins loading an [`Asset`] of type `A` stored at `path` while holding a guard item.
@@ -397,7 +583,27 @@ impl AssetServer {
         settings: impl Fn(&mut S) + Send + Sync + 'static,
         guard: G,
     ) -> Handle<A> {
-        self.load_with_meta_transform(path, Some(loader_settings_meta_transform(settings)), guard)
+        self.load_with_meta_transform(
+            path,
+            Some(loader_settings_meta_transform(settings)),
+            guard,
+            LoadPriority::Normal,
+        )
+    }
+
+    /// Begins loading an [`Asset`] of type `A` stored at `path` like [`AssetServer::load`], but with
+    /// the given `priority`. When the [`AssetServer::descriptor_limit`] is reached, waiting loads
+    /// of higher priority start first, so an asset that is needed right away does not wait behind
+    /// a backlog of less urgent ones.
+    ///
+    /// If the asset is already loading, its load is not reprioritized.
+    #[must_use = "not using the returned strong handle may result in the unexpected release of the asset"]
+    pub fn load_with_priority<'a, A: Asset>(
+        &self,
+        path: impl Into<AssetPath<'a>>,
+        priority: LoadPriority,
+    ) -> Handle<A> {
+        self.load_with_meta_transform(path, None, (), priority)
     }
 
     pub(crate) fn load_with_meta_transform<'a, A: Asset, G: Send + Sync + 'static>(
@@ -405,6 +611,7 @@ impl AssetServer {
         path: impl Into<AssetPath<'a>>,
         meta_transform: Option<MetaTransform>,
         guard: G,
+        priority: LoadPriority,
     ) -> Handle<A> {
         let path = path.into().into_owned();
         let mut infos = self.data.infos.write();
@@ -415,7 +622,7 @@ impl AssetServer {
         );
 
         if should_load {
-            self.spawn_load_task(handle.clone().untyped(), path, infos, guard);
+            self.spawn_load_task(handle.clone().untyped(), path, infos, guard, priority);
         }
 
         handle
@@ -439,7 +646,7 @@ impl AssetServer {
         );
 
         if should_load {
-            self.spawn_load_task(handle.clone(), path, infos, guard);
+            self.spawn_load_task(handle.clone(), path, infos, guard, LoadPriority::Normal);
         }
 
         handle
@@ -451,6 +658,7 @@ impl AssetServer {
         path: AssetPath<'static>,
         infos: RwLockWriteGuard<AssetInfos>,
         guard: G,
+        priority: LoadPriority,
     ) {
         // drop the lock on `AssetInfos` before spawning a task that may block on it in single-threaded
         #[cfg(any(target_arch = "wasm32", not(feature = "multi_threaded")))]
@@ -460,7 +668,7 @@ impl AssetServer {
         let server = self.clone();
         let task = IoTaskPool::get().spawn(async move {
             if let Err(err) = server
-                .load_internal(Some(owned_handle), path, false, None)
+                .load_internal(Some(owned_handle), path, false, None, priority)
                 .await
             {
                 error!("{}", err);
@@ -487,7 +695,8 @@ impl AssetServer {
         path: impl Into<AssetPath<'a>>,
     ) -> Result<UntypedHandle, AssetLoadError> {
         let path: AssetPath = path.into();
-        self.load_internal(None, path, false, None).await
+        self.load_internal(None, path, false, None, LoadPriority::Normal)
+            .await
     }
 
     pub(crate) fn load_unknown_type_with_meta_transform<'a>(
@@ -585,9 +794,16 @@ impl AssetServer {
         path: AssetPath<'a>,
         force: bool,
         meta_transform: Option<MetaTransform>,
+        priority: LoadPriority,
     ) -> Result<UntypedHandle, AssetLoadError> {
         //Wait to acquire asset permit so we don't overload the file io for the os
-        let _guard = self.data.asset_counter.acquire().await;
+        let descriptor_limit = self.data.descriptor_limit_for(path.source());
+        let uncounted = self.data.uncounted.read().contains(&path);
+        let _permit = if uncounted {
+            None
+        } else {
+            Some(descriptor_limit.acquire(priority).await)
+        };
 
         let asset_type_id = input_handle.as_ref().map(UntypedHandle::type_id);
 
@@ -760,7 +976,15 @@ impl AssetServer {
                     .infos
                     .read()
                     .get_path_handles(&path)
-                    .map(|handle| server.load_internal(Some(handle), path.clone(), true, None))
+                    .map(|handle| {
+                        server.load_internal(
+                            Some(handle),
+                            path.clone(),
+                            true,
+                            None,
+                            LoadPriority::Normal,
+                        )
+                    })
                     .collect::<Vec<_>>();
 
                 for result in requests {
@@ -771,7 +995,10 @@ impl AssetServer {
                 }
 
                 if !reloaded && server.data.infos.read().should_reload(&path) {
-                    if let Err(err) = server.load_internal(None, path, true, None).await {
+                    if let Err(err) = server
+                        .load_internal(None, path, true, None, LoadPriority::Normal)
+                        .await
+                    {
                         error!("{}", err);
                     }
                 }
@@ -917,7 +1144,18 @@ impl AssetServer {
         ) -> Result<(), AssetLoadError> {
             let is_dir = reader.is_directory(path).await?;
             if is_dir {
-                let mut path_stream = reader.read_directory(path.as_ref()).await?;
+                let directory_limit = server.data.directory_descriptor_limit();
+                let mut path_stream = match &directory_limit {
+                    // read to the end while the permit is held, as loading the children takes
+                    // permits of its own
+                    Some(limit) => {
+                        let _permit = limit.acquire(LoadPriority::Normal).await;
+                        let children: Vec<PathBuf> =
+                            reader.read_directory(path.as_ref()).await?.collect().await;
+                        Box::new(futures_lite::stream::iter(children)) as Box<PathStream>
+                    }
+                    None => reader.read_directory(path.as_ref()).await?,
+                };
                 while let Some(child_path) = path_stream.next().await {
                     if reader.is_directory(&child_path).await? {
                         Box::pin(load_folder(
@@ -1540,7 +1778,7 @@ impl AssetServer {
         let reader = source.reader();
         match reader.read_meta_bytes(path.path()).await {
             Ok(_) => return Err(WriteDefaultMetaError::MetaAlreadyExists),
-            Err(AssetReaderError::NotFound(_)) => {
+            Err(AssetReaderError::NotFound(_) | AssetReaderError::NotFoundInSearchPaths { .. }) => {
                 // The meta file couldn't be found so just fall through.
             }
             Err(AssetReaderError::Io(err)) => {
@@ -1549,6 +1787,18 @@ impl AssetServer {
             Err(AssetReaderError::HttpError(err)) => {
                 return Err(WriteDefaultMetaError::HttpErrorFromExistingMetaCheck(err))
             }
+            Err(AssetReaderError::DanglingSymlink { link, .. }) => {
+                return Err(WriteDefaultMetaError::DanglingSymlinkFromExistingMetaCheck(
+                    link,
+                ))
+            }
+            Err(AssetReaderError::Shutdown) => {
+                return Err(WriteDefaultMetaError::ShutdownDuringExistingMetaCheck)
+            }
+            Err(AssetReaderError::TruncatedDuringRead { .. }) => {
+                // a meta file was there to be read, so it must not be overwritten
+                return Err(WriteDefaultMetaError::MetaAlreadyExists);
+            }
         }
 
         let writer = source.writer()?;
@@ -1982,4 +2232,8 @@ pub enum WriteDefaultMetaError {
     IoErrorFromExistingMetaCheck(Arc<std::io::Error>),
     #[error("encountered HTTP status {0} when reading the existing meta file")]
     HttpErrorFromExistingMetaCheck(u16),
+    #[error("the existing meta file {} is a symbolic link to a missing target", _0.display())]
+    DanglingSymlinkFromExistingMetaCheck(PathBuf),
+    #[error("the asset reader shut down while reading the existing meta file")]
+    ShutdownDuringExistingMetaCheck,
 }